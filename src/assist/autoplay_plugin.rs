@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use bevy::{log, prelude::*};
+
+use crate::{
+    board::{
+        board::Board, board_changed::BoardChanged, board_plugin::BoardPlugin,
+        pending_board::PendingBoard, settings::BoardSettings,
+    },
+    utils::app_state::AppState,
+};
+
+const MIN_DELAY: Duration = Duration::from_millis(10);
+const MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Lets the bot play the board, toggled with B while playing or from the main menu. The bot
+/// plays proven moves first and the safest guess otherwise, through the same path as clicks.
+pub struct AutoplayPlugin;
+
+impl Plugin for AutoplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Autoplay>()
+            .add_systems(
+                Update,
+                (
+                    Self::autoplay_controls,
+                    Self::play_move
+                        .before(BoardPlugin::victory_validation)
+                        .run_if(|autoplay: Res<Autoplay>| autoplay.enabled)
+                        .run_if(not(resource_exists::<PendingBoard>)),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), Self::stop_autoplay);
+    }
+}
+
+/// Whether the bot plays, and how fast
+#[derive(Resource)]
+pub struct Autoplay {
+    pub enabled: bool,
+    /// Time between two moves, so humans can follow the game
+    pub delay: Duration,
+    /// Time since the last move
+    pub waited: Duration,
+}
+
+impl Default for Autoplay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delay: Duration::from_millis(250),
+            waited: Duration::ZERO,
+        }
+    }
+}
+
+impl AutoplayPlugin {
+    /// B starts or stops the bot, [ and ] make it play faster or slower
+    pub fn autoplay_controls(keys: Res<ButtonInput<KeyCode>>, mut autoplay: ResMut<Autoplay>) {
+        if keys.just_pressed(KeyCode::KeyB) {
+            autoplay.enabled = !autoplay.enabled;
+            log::info!("Autoplay: {}", autoplay.enabled);
+        }
+        if keys.just_pressed(KeyCode::BracketLeft) {
+            autoplay.delay = (autoplay.delay / 2).max(MIN_DELAY);
+            log::info!("Autoplay delay: {:?}", autoplay.delay);
+        }
+        if keys.just_pressed(KeyCode::BracketRight) {
+            autoplay.delay = (autoplay.delay * 2).min(MAX_DELAY);
+            log::info!("Autoplay delay: {:?}", autoplay.delay);
+        }
+    }
+
+    /// Plays the next move once the delay is over. Games the bot played in are assisted.
+    pub fn play_move(
+        time: Res<Time>,
+        mut autoplay: ResMut<Autoplay>,
+        mut commands: Commands,
+        mut board: Single<&mut Board>,
+        board_settings: Res<BoardSettings>,
+        mut board_changed_event: EventWriter<BoardChanged>,
+    ) {
+        autoplay.waited += time.delta();
+        if autoplay.waited < autoplay.delay {
+            return;
+        }
+        autoplay.waited = Duration::ZERO;
+
+        let Some(hint) = board.next_move(&board_settings) else {
+            log::info!("Autoplay has no move left, the flags block every tile, stopping");
+            autoplay.enabled = false;
+            return;
+        };
+
+        log::info!("Autoplay: {:?}", hint);
+        board.assisted = true;
+        let outcome = BoardPlugin::apply_action(
+            &mut commands,
+            &mut board,
+            hint.action(),
+            &board_settings,
+            &mut board_changed_event,
+        );
+        if outcome.is_some_and(|outcome| outcome.is_empty()) {
+            log::info!("Autoplay move {:?} changed nothing, stopping", hint);
+            autoplay.enabled = false;
+        }
+    }
+
+    /// Stops the bot when the game ends, so it only plays the games it was started for
+    pub fn stop_autoplay(mut autoplay: ResMut<Autoplay>) {
+        autoplay.enabled = false;
+        autoplay.waited = Duration::ZERO;
+    }
+}
@@ -0,0 +1,203 @@
+use bevy::{log, platform::collections::HashSet, prelude::*};
+
+use crate::{
+    board::{
+        board::Board, board_changed::BoardChanged, board_plugin::BoardPlugin,
+        coordinates::Coordinates, settings::BoardSettings, sprites::Sprites,
+    },
+    solver::mine_probabilities::MineProbabilities,
+    ui::settings::UiSettings,
+    utils::app_state::AppState,
+};
+
+/// Tints every unknown tile by its chance of holding a mine, toggled with P while playing
+pub struct HeatmapPlugin;
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Heatmap>()
+            .add_systems(
+                Update,
+                (
+                    Self::toggle_heatmap,
+                    Self::draw_heatmap
+                        .after(BoardPlugin::update_board)
+                        .run_if(on_event::<BoardChanged>.or(resource_changed::<Heatmap>)),
+                    Self::hover_heatmap,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), Self::hide_heatmap);
+    }
+}
+
+/// Whether the heatmap is shown over the board
+#[derive(Resource, Default)]
+pub struct Heatmap {
+    pub shown: bool,
+    /// Probabilities of the current board, dropped whenever the board changes
+    probabilities: Option<MineProbabilities>,
+}
+
+/// Tint over a tile, drawn above the tile's foreground. Every tile keeps its tint once drawn,
+/// hidden while the tile is known or the heatmap is off.
+#[derive(Component)]
+pub struct HeatmapTile;
+
+/// Percentage over a tile, only visible while the tile is hovered
+#[derive(Component)]
+pub struct HeatmapLabel;
+
+impl HeatmapPlugin {
+    /// Showing the heatmap marks the game as assisted
+    pub fn toggle_heatmap(
+        keys: Res<ButtonInput<KeyCode>>,
+        mut heatmap: ResMut<Heatmap>,
+        mut board: Single<&mut Board>,
+    ) {
+        if !keys.just_pressed(KeyCode::KeyP) {
+            return;
+        }
+
+        heatmap.shown = !heatmap.shown;
+        if heatmap.shown {
+            board.assisted = true;
+        }
+        log::info!("Heatmap shown: {}", heatmap.shown);
+    }
+
+    /// Updates the tints from the current board. The probabilities are only computed while the
+    /// heatmap is shown, once per board change, and every tile keeps its tint across updates.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_heatmap(
+        mut commands: Commands,
+        mut heatmap: ResMut<Heatmap>,
+        mut board_changes: EventReader<BoardChanged>,
+        board: Single<&Board>,
+        board_settings: Res<BoardSettings>,
+        ui_settings: Res<UiSettings>,
+        sprites: Res<Sprites>,
+        mut tiles: Query<
+            (&Coordinates, &mut Sprite, &mut Visibility, &Children),
+            With<HeatmapTile>,
+        >,
+        mut labels: Query<&mut Text2d, With<HeatmapLabel>>,
+    ) {
+        if !board_changes.is_empty() {
+            board_changes.clear();
+            heatmap.bypass_change_detection().probabilities = None;
+        }
+        if !heatmap.shown {
+            for (_, _, mut visibility, _) in &mut tiles {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+            return;
+        }
+
+        let probabilities = heatmap
+            .bypass_change_detection()
+            .probabilities
+            .get_or_insert_with(|| board.mine_probabilities(&board_settings));
+
+        let mut tinted = HashSet::new();
+        for (coordinates, mut sprite, mut visibility, children) in &mut tiles {
+            let Some(probability) = probabilities.get(coordinates) else {
+                visibility.set_if_neq(Visibility::Hidden);
+                continue;
+            };
+
+            let probability = probability as f32;
+            sprite.color = Self::tint(probability);
+            visibility.set_if_neq(Visibility::Inherited);
+            let mut labels = labels.iter_many_mut(children);
+            while let Some(mut label) = labels.fetch_next() {
+                label.0 = Self::label(probability);
+            }
+            tinted.insert(*coordinates);
+        }
+
+        let box_size = Vec2::new(ui_settings.tile_size, ui_settings.tile_size);
+        for (coordinates, probability) in &probabilities.cells {
+            let Some(entities) = board.tile_entities.get(coordinates) else {
+                continue;
+            };
+            if tinted.contains(coordinates) {
+                continue;
+            }
+
+            let probability = *probability as f32;
+            commands.spawn((
+                HeatmapTile,
+                *coordinates,
+                Sprite::from_color(Self::tint(probability), box_size),
+                Transform::from_translation(Vec3::new(0.0, 0.0, 2.0)),
+                ChildOf(entities.background),
+                children![(
+                    HeatmapLabel,
+                    *coordinates,
+                    Text2d::new(Self::label(probability)),
+                    TextFont::from_font(sprites.font.clone()).with_font_size(14.0),
+                    TextLayout::new_with_justify(JustifyText::Center),
+                    Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
+                    Visibility::Hidden,
+                )],
+            ));
+        }
+        log::info!(
+            "Drew heatmap over {} tiles{}",
+            probabilities.cells.len(),
+            if probabilities.exact {
+                ""
+            } else {
+                " (estimated)"
+            }
+        );
+    }
+
+    fn tint(probability: f32) -> Color {
+        Color::srgba(probability, 1.0 - probability, 0.0, 0.45)
+    }
+
+    fn label(probability: f32) -> String {
+        format!("{:.0}%", probability * 100.0)
+    }
+
+    /// Shows the percentage of the tile under the cursor
+    pub fn hover_heatmap(
+        windows: Query<&Window>,
+        camera: Single<(&Camera, &GlobalTransform)>,
+        board: Single<(&Board, &GlobalTransform)>,
+        ui_settings: Res<UiSettings>,
+        mut labels: Query<(&Coordinates, &mut Visibility), With<HeatmapLabel>>,
+    ) {
+        let (camera, camera_transform) = *camera;
+        let (board, board_transform) = *board;
+        let hovered = windows
+            .single()
+            .ok()
+            .and_then(|window| window.cursor_position())
+            .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+            .and_then(|position| board.tile_at(position, board_transform, &ui_settings));
+
+        for (coordinates, mut visibility) in &mut labels {
+            visibility.set_if_neq(if Some(*coordinates) == hovered {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            });
+        }
+    }
+
+    /// Hides the heatmap when the game ends, the next game starts without it. The tints go away
+    /// with the board.
+    pub fn hide_heatmap(
+        mut heatmap: ResMut<Heatmap>,
+        mut tiles: Query<&mut Visibility, With<HeatmapTile>>,
+    ) {
+        *heatmap = Heatmap::default();
+        for mut visibility in &mut tiles {
+            visibility.set_if_neq(Visibility::Hidden);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::f32::consts::TAU;
+
+use bevy::{color::palettes::css::*, log, prelude::*};
+
+use crate::{
+    board::{
+        board::Board, board_changed::BoardChanged, board_plugin::BoardPlugin,
+        settings::BoardSettings, sprites::Sprites,
+    },
+    solver::hint::Hint,
+    ui::settings::UiSettings,
+    utils::app_state::AppState,
+};
+
+/// Width of the outline drawn around a hinted tile
+const OUTLINE_WIDTH: f32 = 3.0;
+/// Outline pulses per second
+const PULSE_RATE: f32 = 1.5;
+
+/// Outlines the next move when H is pressed while playing
+pub struct HintPlugin;
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                Self::clear_hint
+                    .after(BoardPlugin::update_board)
+                    .run_if(on_event::<BoardChanged>),
+                Self::show_hint,
+                Self::pulse_hint,
+            )
+                .chain()
+                .run_if(in_state(AppState::InGame)),
+        )
+        .add_systems(OnExit(AppState::InGame), Self::clear_hint);
+    }
+}
+
+/// Outline around the hinted tile, removed once the board changes
+#[derive(Component)]
+pub struct HintOutline;
+
+impl HintPlugin {
+    pub fn show_hint(
+        keys: Res<ButtonInput<KeyCode>>,
+        mut commands: Commands,
+        mut board: Single<&mut Board>,
+        board_settings: Res<BoardSettings>,
+        ui_settings: Res<UiSettings>,
+        sprites: Res<Sprites>,
+        outlines: Query<Entity, With<HintOutline>>,
+    ) {
+        if !keys.just_pressed(KeyCode::KeyH) {
+            return;
+        }
+
+        for entity in &outlines {
+            commands.entity(entity).despawn();
+        }
+        let Some(hint) = board.hint(&board_settings) else {
+            log::info!("No hint available");
+            return;
+        };
+        let Some(entities) = board.tile_entities.get(&hint.coordinates()) else {
+            return;
+        };
+        log::info!("Hint {} of this game: {:?}", board.actions.hints, hint);
+
+        let color: Color = match hint {
+            Hint::Safe(_) => LIME.into(),
+            Hint::Mine(_) => RED.into(),
+            Hint::Guess { .. } => YELLOW.into(),
+        };
+        let size = ui_settings.tile_size;
+        let offset = (size - OUTLINE_WIDTH) / 2.0;
+        let side = |x: f32, y: f32, width: f32, height: f32| {
+            (
+                Sprite::from_color(color, Vec2::new(width, height)),
+                Transform::from_translation(Vec3::new(x, y, 0.0)),
+            )
+        };
+
+        let mut outline = commands.spawn((
+            HintOutline,
+            Transform::from_translation(Vec3::new(0.0, 0.0, 3.0)),
+            Visibility::default(),
+            ChildOf(entities.background),
+            children![
+                side(0.0, offset, size, OUTLINE_WIDTH),
+                side(0.0, -offset, size, OUTLINE_WIDTH),
+                side(-offset, 0.0, OUTLINE_WIDTH, size),
+                side(offset, 0.0, OUTLINE_WIDTH, size),
+            ],
+        ));
+        if let Hint::Guess { probability, .. } = hint {
+            outline.with_child((
+                Text2d::new(format!("{:.0}%", probability * 100.0)),
+                TextColor(color),
+                TextFont::from_font(sprites.font.clone()).with_font_size(14.0),
+                Transform::from_translation(Vec3::new(0.0, size * 0.75, 0.0)),
+            ));
+        }
+    }
+
+    /// Grows and shrinks the outline so it stands out from the board
+    pub fn pulse_hint(time: Res<Time>, mut outlines: Query<&mut Transform, With<HintOutline>>) {
+        let scale = 1.0 + 0.1 * (time.elapsed_secs() * PULSE_RATE * TAU).sin();
+        for mut transform in &mut outlines {
+            transform.scale = Vec3::new(scale, scale, 1.0);
+        }
+    }
+
+    pub fn clear_hint(mut commands: Commands, outlines: Query<Entity, With<HintOutline>>) {
+        for entity in &outlines {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod autoplay_plugin;
+pub mod heatmap_plugin;
+pub mod hint_plugin;
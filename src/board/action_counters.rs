@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use crate::board::game_action::GameAction;
+
+/// Number of moves the player made, only actions that changed the board are counted
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionCounters {
+    pub reveals: u32,
+    pub flags: u32,
+    pub chords: u32,
+    pub undos: u32,
+    pub redos: u32,
+    /// Hints asked for, they are not moves and do not count toward the total
+    #[serde(default)]
+    pub hints: u32,
+}
+
+impl ActionCounters {
+    pub fn count(&mut self, action: GameAction) {
+        match action {
+            GameAction::Reveal(_) => self.reveals += 1,
+            GameAction::ToggleFlag(_) => self.flags += 1,
+            GameAction::Chord(_) => self.chords += 1,
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.reveals + self.flags + self.chords
+    }
+}
@@ -0,0 +1,39 @@
+use crate::board::{coordinates::Coordinates, tile_change::TileChange};
+
+/// What applying a `GameAction` did to the board
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ActionOutcome {
+    /// Tiles revealed by the action in BFS order, mines hit included
+    pub revealed: Vec<Coordinates>,
+    /// Tiles whose flag was placed or removed
+    pub flags_changed: Vec<Coordinates>,
+    /// Mines revealed by the action
+    pub mines_hit: Vec<Coordinates>,
+    /// Every tile transition made by the action, used to undo it
+    pub changes: Vec<TileChange>,
+    /// State of the game after the action
+    pub status: GameStatus,
+}
+
+impl ActionOutcome {
+    /// Every tile changed by the action
+    pub fn changed(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        self.revealed
+            .iter()
+            .chain(self.flags_changed.iter())
+            .copied()
+    }
+
+    /// Whether the action left the board untouched
+    pub fn is_empty(&self) -> bool {
+        self.revealed.is_empty() && self.flags_changed.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameStatus {
+    #[default]
+    Playing,
+    Won,
+    Lost,
+}
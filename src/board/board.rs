@@ -1,34 +1,419 @@
-use bevy::{ecs::system::QueryLens, prelude::*};
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{log, platform::collections::HashMap, prelude::*};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 
 use crate::{
-    UiSettings,
-    board::{coordinates::Coordinates, tile_map::TileMap},
+    board::{
+        action_counters::ActionCounters, action_outcome::ActionOutcome, coordinates::Coordinates,
+        game_action::GameAction, history::History, no_guess_error::NoGuessError,
+        settings::BoardSettings, tile_map::TileMap,
+    },
+    replay::replay::{Replay, ReplayStep},
+    save::save_game::{SAVE_VERSION, SaveGame},
+    solver::{
+        hint::{Hint, find_hint},
+        mine_probabilities::MineProbabilities,
+        no_guess::generate_no_guess,
+        probability::probabilities_for_state,
+        visible_state::VisibleState,
+    },
+    ui::settings::UiSettings,
     utils::bounds2::Bounds2,
 };
 
+/// Layouts tried for a board without guesses before giving up
+const NO_GUESS_ATTEMPTS: u32 = 2_000;
+
 #[derive(Component)]
 pub struct Board {
     pub tile_map: TileMap,
+    /// Seed the board was generated from
+    pub seed: u64,
+    /// Generator seeded with `seed`, used to place the mines on the first reveal
+    pub rng: ChaCha8Rng,
+    /// Mines are only placed once the first tile is revealed
+    pub mines_placed: bool,
+    /// Entities drawing each tile
+    pub tile_entities: HashMap<Coordinates, TileEntities>,
+    /// Applied actions that can be undone and redone
+    pub history: History,
+    /// Set once the player used help such as undo, assisted games do not count toward records
+    pub assisted: bool,
+    /// Time spent playing this board
+    pub elapsed: Duration,
+    /// Moves made on this board
+    pub actions: ActionCounters,
+    /// Every action applied to this board, saved as a replay when the game ends
+    pub recording: Replay,
+    /// File the replay was saved to. A loss undone and lost again updates it rather than adding
+    /// another replay of the same game.
+    pub replay_path: Option<PathBuf>,
+}
+
+/// The two entities drawing a tile: the background shows whether the tile is covered, the
+/// foreground holds the flag, bomb or number on top of it
+#[derive(Clone, Copy, Debug)]
+pub struct TileEntities {
+    pub background: Entity,
+    pub foreground: Entity,
 }
 
 impl Board {
-    pub fn find_colliding_tile_coords(
+    /// Creates an empty board, its mines are placed on the first reveal. The tile entities are
+    /// added once they are spawned.
+    pub fn new(board_settings: &BoardSettings) -> Self {
+        let recorded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+
+        let mut tile_map = TileMap::empty(board_settings.board_width, board_settings.board_height);
+        tile_map.rules = board_settings.rules;
+
+        Self {
+            tile_map,
+            seed: board_settings.seed,
+            rng: ChaCha8Rng::seed_from_u64(board_settings.seed),
+            mines_placed: false,
+            tile_entities: HashMap::new(),
+            history: History::default(),
+            assisted: false,
+            elapsed: Duration::ZERO,
+            actions: ActionCounters::default(),
+            recording: Replay::new(board_settings.clone(), recorded_at),
+            replay_path: None,
+        }
+    }
+
+    /// Applies an action through the game rules, placing the mines first when the action is the
+    /// first reveal of the game. Only actions that changed the board are recorded. Boards without
+    /// guesses must get their mines from `generate_mines_task` before their first reveal.
+    pub fn apply(&mut self, action: GameAction, board_settings: &BoardSettings) -> ActionOutcome {
+        if self.places_mines(action) {
+            self.place_mines(action.coordinates(), board_settings);
+        }
+
+        let outcome = self.tile_map.apply(action);
+        if !outcome.is_empty() {
+            self.actions.count(action);
+            self.history.record(action, outcome.changes.clone());
+            self.record(ReplayStep::Action(action));
+        }
+        outcome
+    }
+
+    /// Reverts the last action, returning the tiles it changed. Undoing a loss also returns the
+    /// wrong flags, which are no longer shown as mistakes.
+    pub fn undo(&mut self) -> Option<Vec<Coordinates>> {
+        let was_lost = self.tile_map.has_lost();
+        let mut changed = self.history.undo(&mut self.tile_map)?;
+        if was_lost && !self.tile_map.has_lost() {
+            changed.extend(self.tile_map.wrong_flags());
+        }
+        self.assisted = true;
+        self.actions.undos += 1;
+        self.record(ReplayStep::Undo);
+        Some(changed)
+    }
+
+    /// Applies the last undone action again, returning the tiles it changed
+    pub fn redo(&mut self) -> Option<Vec<Coordinates>> {
+        let changed = self.history.redo(&mut self.tile_map)?;
+        self.actions.redos += 1;
+        self.record(ReplayStep::Redo);
+        Some(changed)
+    }
+
+    /// Reveals the mines after one was hit, returning them along with the wrong flags to redraw.
+    /// The reveal belongs to the losing action, so undoing that action covers the mines again.
+    pub fn reveal_on_defeat(&mut self) -> Vec<Coordinates> {
+        let changes = self.tile_map.reveal_mines();
+        let mut changed: Vec<Coordinates> =
+            changes.iter().map(|change| change.coordinates).collect();
+        changed.extend(self.tile_map.wrong_flags());
+        self.history.extend_last(changes);
+        changed
+    }
+
+    /// Plays on a board loaded from a file instead of placing random mines, returning every tile
+    /// so they can be drawn
+    pub fn load_preset(&mut self, mut tile_map: TileMap) -> Vec<Coordinates> {
+        tile_map.rules = self.tile_map.rules;
+        self.recording.preset = Some(tile_map.clone());
+        self.tile_map = tile_map;
+        self.mines_placed = true;
+        self.tile_map.coordinates().collect()
+    }
+
+    /// Captures the game in progress so it can be continued later
+    pub fn to_save(&self, board_settings: &BoardSettings) -> SaveGame {
+        SaveGame {
+            version: SAVE_VERSION,
+            settings: board_settings.clone(),
+            tile_map: self.tile_map.clone(),
+            mines_placed: self.mines_placed,
+            elapsed: self.elapsed,
+            actions: self.actions,
+            assisted: self.assisted,
+            recording: self.recording.clone(),
+        }
+    }
+
+    /// Continues a saved game on this board, returning every tile so they can be redrawn. The
+    /// undo history is not saved and starts empty.
+    pub fn restore(&mut self, save: SaveGame) -> Vec<Coordinates> {
+        self.tile_map = save.tile_map;
+        self.mines_placed = save.mines_placed;
+        self.elapsed = save.elapsed;
+        self.actions = save.actions;
+        self.assisted = save.assisted;
+        self.recording = save.recording;
+        self.history = History::default();
+        self.tile_map.coordinates().collect()
+    }
+
+    /// Flags the mines left unflagged once the game is won, as part of the winning action
+    pub fn flag_on_victory(&mut self) -> Vec<Coordinates> {
+        let changes = self.tile_map.flag_remaining_mines();
+        let changed = changes.iter().map(|change| change.coordinates).collect();
+        self.history.extend_last(changes);
+        changed
+    }
+
+    /// Mines on the board. Before the first reveal the mines are not placed yet, so their count
+    /// comes from the settings.
+    fn mine_count(&self, board_settings: &BoardSettings) -> usize {
+        if self.mines_placed {
+            self.tile_map.bomb_count as usize
+        } else {
+            board_settings.mine_count as usize
+        }
+    }
+
+    /// Chance of every unknown tile to hold a mine
+    pub fn mine_probabilities(&self, board_settings: &BoardSettings) -> MineProbabilities {
+        let mut state = VisibleState::from_tile_map(&self.tile_map);
+        state.mine_count = self.mine_count(board_settings);
+        probabilities_for_state(&state)
+    }
+
+    /// Next move the bot would play
+    pub fn next_move(&self, board_settings: &BoardSettings) -> Option<Hint> {
+        find_hint(&self.tile_map, self.mine_count(board_settings))
+    }
+
+    /// Next move suggested to the player, counted as a hint and marking the game as assisted
+    pub fn hint(&mut self, board_settings: &BoardSettings) -> Option<Hint> {
+        let hint = self.next_move(board_settings)?;
+        self.actions.hints += 1;
+        self.assisted = true;
+        Some(hint)
+    }
+
+    fn record(&mut self, step: ReplayStep) {
+        self.recording.record(self.elapsed.as_secs_f32(), step);
+    }
+
+    /// Whether the action is the first reveal of the game, which places the mines
+    pub fn places_mines(&self, action: GameAction) -> bool {
+        !self.mines_placed
+            && matches!(action, GameAction::Reveal(_))
+            && self
+                .tile_map
+                .get(&action.coordinates())
+                .is_some_and(|tile| tile.state.is_covered())
+    }
+
+    /// Places the mines while keeping the tiles excluded by the first click policy free
+    fn place_mines(&mut self, first_click: Coordinates, board_settings: &BoardSettings) {
+        debug_assert!(
+            !board_settings.no_guess,
+            "boards without guesses are generated by generate_mines_task"
+        );
+        let excluded = board_settings
+            .first_click
+            .excluded_coordinates(&self.tile_map, first_click);
+        self.tile_map
+            .set_bombs(board_settings.mine_count, &excluded, &mut self.rng);
+        self.mines_placed = true;
+        self.log_mines(first_click, board_settings);
+    }
+
+    /// Generates the mines of a board without guesses from a copy of the generator, so it can
+    /// run off the frame. The result goes to `place_generated_mines`.
+    pub fn generate_mines_task(
+        &self,
+        first_click: Coordinates,
+        board_settings: &BoardSettings,
+    ) -> impl Future<Output = GeneratedMines> + Send + 'static {
+        let board_settings = board_settings.clone();
+        let excluded = board_settings
+            .first_click
+            .excluded_coordinates(&self.tile_map, first_click);
+        let mut rng = self.rng.clone();
+        async move {
+            let mines = generate_without_guesses(&board_settings, &excluded, first_click, &mut rng);
+            GeneratedMines { mines, rng }
+        }
+    }
+
+    /// Places mines generated by `generate_mines_task`, continuing with its generator so the
+    /// board stays reproducible from its seed
+    pub fn place_generated_mines(
+        &mut self,
+        first_click: Coordinates,
+        mines: &[Coordinates],
+        rng: ChaCha8Rng,
+        board_settings: &BoardSettings,
+    ) {
+        self.rng = rng;
+        self.tile_map.set_mines(mines);
+        self.mines_placed = true;
+        self.log_mines(first_click, board_settings);
+    }
+
+    fn log_mines(&self, first_click: Coordinates, board_settings: &BoardSettings) {
+        log::info!(
+            "Placed mines around first click at {} ({:?})",
+            first_click,
+            board_settings.first_click
+        );
+        log::info!("{}", self.tile_map.console_output());
+    }
+
+    /// Finds the tile under a world position by mapping it into the board's local space, where
+    /// tile `(x, y)` is centered on `(x, y) * (tile_size + tile_spacing)`. This follows any
+    /// translation, rotation or scale of the board.
+    pub fn tile_at(
         &self,
         point: Vec2,
-        tiles: &mut QueryLens<(Entity, &GlobalTransform, &Coordinates)>,
+        board_transform: &GlobalTransform,
         ui_settings: &UiSettings,
-    ) -> Option<(Entity, Coordinates)> {
-        for (entity, transform, coords) in &mut tiles.query() {
-            let bounds = Bounds2::from_center_size(
-                transform.translation().truncate(),
-                Vec2::splat(ui_settings.tile_size + ui_settings.tile_spacing),
-            );
-
-            if bounds.contains(point) {
-                return Some((entity, coords.clone()));
-            }
+    ) -> Option<Coordinates> {
+        let step = ui_settings.tile_size + ui_settings.tile_spacing;
+        let local = board_transform
+            .affine()
+            .inverse()
+            .transform_point3(point.extend(0.0))
+            .truncate();
+
+        let bounds = Bounds2::from_min_size(
+            Vec2::splat(-step / 2.0),
+            Vec2::new(
+                self.tile_map.width() as f32 * step,
+                self.tile_map.height() as f32 * step,
+            ),
+        );
+        if !bounds.contains(local) {
+            return None;
         }
 
-        None
+        let cell = (local / step + Vec2::splat(0.5)).floor();
+        let coords = Coordinates {
+            x: cell.x as u16,
+            y: cell.y as u16,
+        };
+
+        self.tile_map.coords_in_bounds(coords).then_some(coords)
+    }
+}
+
+/// Mines of a board without guesses generated off the frame, with the generator state after
+/// drawing them
+pub struct GeneratedMines {
+    pub mines: Result<Vec<Coordinates>, NoGuessError>,
+    pub rng: ChaCha8Rng,
+}
+
+/// Draws layouts until one needs no guess, logging the progress
+fn generate_without_guesses(
+    board_settings: &BoardSettings,
+    excluded: &[Coordinates],
+    first_click: Coordinates,
+    rng: &mut ChaCha8Rng,
+) -> Result<Vec<Coordinates>, NoGuessError> {
+    let mines = generate_no_guess(
+        board_settings.board_width,
+        board_settings.board_height,
+        board_settings.mine_count,
+        excluded,
+        first_click,
+        NO_GUESS_ATTEMPTS,
+        rng,
+        |attempt| {
+            if attempt % 100 == 0 {
+                log::info!(
+                    "Regenerating board without guesses ({}/{})...",
+                    attempt,
+                    NO_GUESS_ATTEMPTS
+                );
+            }
+        },
+    )?;
+    log::info!("Found a board without guesses");
+    Ok(mines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tile_at_follows_a_scaled_and_translated_board() {
+        let settings = BoardSettings::new(4, 3, 0).unwrap();
+        let board = Board::new(&settings);
+        let ui_settings = UiSettings::default();
+        assert_eq!(ui_settings.tile_size + ui_settings.tile_spacing, 32.0);
+        let transform = GlobalTransform::from(
+            Transform::from_xyz(100.0, -50.0, 0.0).with_scale(Vec3::new(2.0, 2.0, 1.0)),
+        );
+        let tile_at = |x: f32, y: f32| board.tile_at(Vec2::new(x, y), &transform, &ui_settings);
+        let coords = |x: u16, y: u16| Some(Coordinates { x, y });
+
+        // Tiles are 64 world units wide, tile (0, 0) spans 68..132 and -82..-18
+        assert_eq!(tile_at(100.0, -50.0), coords(0, 0));
+        assert_eq!(tile_at(68.5, -81.5), coords(0, 0));
+        assert_eq!(tile_at(131.5, -50.0), coords(0, 0));
+        assert_eq!(tile_at(132.5, -50.0), coords(1, 0));
+        assert_eq!(tile_at(100.0, -17.5), coords(0, 1));
+        assert_eq!(tile_at(323.5, 109.5), coords(3, 2));
+
+        assert_eq!(tile_at(67.5, -50.0), None);
+        assert_eq!(tile_at(100.0, -82.5), None);
+        assert_eq!(tile_at(324.5, -50.0), None);
+        assert_eq!(tile_at(100.0, 110.5), None);
+    }
+
+    #[test]
+    fn actions_that_change_nothing_are_not_recorded() {
+        let settings = BoardSettings::new(9, 9, 10).unwrap().with_seed(7);
+        let mut board = Board::new(&settings);
+        let first_click = Coordinates { x: 4, y: 4 };
+
+        assert!(
+            !board
+                .apply(GameAction::Reveal(first_click), &settings)
+                .is_empty()
+        );
+        assert!(
+            board
+                .apply(GameAction::Reveal(first_click), &settings)
+                .is_empty()
+        );
+        assert!(
+            board
+                .apply(GameAction::ToggleFlag(first_click), &settings)
+                .is_empty()
+        );
+
+        assert_eq!(board.recording.events.len(), 1);
+        assert_eq!(
+            board.recording.events[0].step,
+            ReplayStep::Action(GameAction::Reveal(first_click))
+        );
     }
 }
@@ -1,4 +1,9 @@
 use bevy::prelude::*;
 
+use crate::board::coordinates::Coordinates;
+
+/// Sent whenever tiles of the board change, only the listed tiles are redrawn
 #[derive(Event)]
-pub struct BoardChanged;
+pub struct BoardChanged {
+    pub coordinates: Vec<Coordinates>,
+}
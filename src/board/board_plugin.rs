@@ -1,14 +1,27 @@
-use bevy::{color::palettes::css::*, ecs::system::QueryLens, log, prelude::*};
+use bevy::{
+    color::palettes::css::*,
+    ecs::system::QueryLens,
+    log,
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, block_on, futures_lite::future::poll_once},
+};
 
 use crate::{
-    BoardSettings, UiSettings,
     board::{
-        board::Board,
+        action_outcome::ActionOutcome,
+        board::{Board, TileEntities},
         board_changed::BoardChanged,
         coordinates::Coordinates,
+        game_action::GameAction,
+        pending_board::{PendingBoard, PendingBoardText},
+        preset_board::PresetBoard,
+        settings::BoardSettings,
         sprites::Sprites,
-        tile::{tile_state::TileState, tile_type::TileType},
-        tile_map::TileMap,
+        tile::{tile_state::TileState, tile_visual::TileVisual},
+    },
+    ui::{
+        menu_error::MenuError,
+        settings::{ChordTrigger, UiSettings},
     },
     utils::app_state::AppState,
 };
@@ -19,23 +32,34 @@ impl Plugin for BoardPlugin {
     fn build(&self, app: &mut App) {
         app.insert_state::<AppState>(AppState::default())
             .add_systems(OnEnter(AppState::MainMenu), Self::clear_board)
+            .add_systems(OnExit(AppState::InGame), Self::cancel_pending_board)
             .add_event::<BoardChanged>()
             .add_systems(
-                OnEnter(AppState::InGame),
+                OnTransition {
+                    exited: AppState::MainMenu,
+                    entered: AppState::InGame,
+                },
                 (Self::clear_board, Self::create_board).chain(),
             )
             .add_systems(
                 Update,
                 (
-                    (Self::find_safe_start, Self::left_click_tile).chain(),
-                    Self::right_click_tile,
-                    Self::victory_validation,
-                    Self::defeat_validation,
+                    Self::finish_pending_board.run_if(resource_exists::<PendingBoard>),
+                    (
+                        (Self::click_tile, Self::undo_redo)
+                            .run_if(not(resource_exists::<PendingBoard>)),
+                        Self::tick_clock,
+                    ),
+                    (Self::victory_validation, Self::defeat_validation)
+                        .run_if(on_event::<BoardChanged>),
                     Self::update_board,
+                    Self::leave_on_error.run_if(resource_exists_and_changed::<MenuError>),
                 )
+                    .chain()
                     .run_if(in_state(AppState::InGame)),
             )
-            .add_systems(OnEnter(AppState::Defeat), Self::update_board);
+            .add_systems(OnEnter(AppState::Defeat), Self::update_board)
+            .add_systems(Update, Self::undo_redo.run_if(in_state(AppState::Defeat)));
     }
 }
 
@@ -43,156 +67,282 @@ impl BoardPlugin {
     fn get_tile_coords(
         windows: Query<&Window>,
         mut camera_query_lens: QueryLens<(&Camera, &GlobalTransform)>,
-        mut tiles: QueryLens<(Entity, &GlobalTransform, &Coordinates)>,
         board: &Board,
+        board_transform: &GlobalTransform,
         ui_settings: &Res<UiSettings>,
     ) -> Option<Coordinates> {
         let window = windows.single().expect("No window found");
         let camera_query = camera_query_lens.query();
         let (camera, camera_transform) = camera_query.single().expect("No camera found");
-        let cursor = window.cursor_position();
-        if cursor.is_none() {
-            return None;
-        }
-        let cursor = cursor.unwrap();
+        let cursor = window.cursor_position()?;
         let world_position = camera
             .viewport_to_world_2d(camera_transform, cursor)
             .expect("Failed to convert viewport to world");
-        let Some((_, coords)) = board.find_colliding_tile_coords(
-            world_position,
-            &mut tiles.query().transmute_lens(),
-            &ui_settings,
-        ) else {
+        let Some(coords) = board.tile_at(world_position, board_transform, ui_settings) else {
             log::info!("No tile found at position {:?}", world_position);
             return None;
         };
 
-        Some(coords.clone())
+        Some(coords)
     }
 
-    /// When the first tile is clicked, generate new boards until there is at least one empty tile under the cursor
-    pub fn find_safe_start(
-        mouse_input: Res<ButtonInput<MouseButton>>,
-        windows: Query<&Window>,
-        mut camera: Query<(&Camera, &GlobalTransform)>,
-        board: Single<&mut Board>,
-        mut tiles: Query<(Entity, &GlobalTransform, &Coordinates)>,
-        ui_settings: Res<UiSettings>,
-    ) {
-        if !board.tile_map.is_pristine() {
-            return;
+    /// Applies an action to the board and sends the tiles it changed to the renderer. The first
+    /// reveal of a board without guesses starts generating it off the frame and is applied by
+    /// `finish_pending_board` once the mines are placed.
+    pub fn apply_action(
+        commands: &mut Commands,
+        board: &mut Board,
+        action: GameAction,
+        board_settings: &BoardSettings,
+        board_changed_event: &mut EventWriter<BoardChanged>,
+    ) -> Option<ActionOutcome> {
+        if board_settings.no_guess && board.places_mines(action) {
+            Self::start_pending_board(commands, board, action, board_settings);
+            return None;
         }
 
-        if !mouse_input.just_pressed(MouseButton::Left) {
-            return;
+        let outcome = board.apply(action, board_settings);
+        if !outcome.is_empty() {
+            board_changed_event.write(BoardChanged {
+                coordinates: outcome.changed().collect(),
+            });
         }
 
-        let mut board = board.into_inner();
-        let Some(coords) = Self::get_tile_coords(
-            windows,
-            camera.transmute_lens(),
-            tiles.transmute_lens(),
-            &board,
-            &ui_settings,
-        ) else {
-            return;
-        };
+        Some(outcome)
+    }
 
-        while let Some(tile) = board.tile_map.at(&coords) {
-            if tile.r#type.is_empty() {
-                break;
+    /// Starts generating a board without guesses for its first reveal, showing that the board
+    /// is on its way
+    pub fn start_pending_board(
+        commands: &mut Commands,
+        board: &Board,
+        action: GameAction,
+        board_settings: &BoardSettings,
+    ) {
+        let generation = board.generate_mines_task(action.coordinates(), board_settings);
+        let task = AsyncComputeTaskPool::get().spawn(generation);
+        commands.insert_resource(PendingBoard { action, task });
+        commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.),
+                width: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            PendingBoardText,
+            children![(
+                Text::new("Generating a board without guesses..."),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            )],
+        ));
+    }
+
+    /// Places the mines of a board without guesses once generated, returning the reveal that
+    /// started it. A board that could not be generated is reported on the main menu.
+    pub fn place_pending_mines(
+        commands: &mut Commands,
+        pending: &mut PendingBoard,
+        board: &mut Board,
+        board_settings: &BoardSettings,
+        texts: &Query<Entity, With<PendingBoardText>>,
+    ) -> Option<GameAction> {
+        let generated = block_on(poll_once(&mut pending.task))?;
+        commands.remove_resource::<PendingBoard>();
+        for entity in texts {
+            commands.entity(entity).despawn();
+        }
+
+        match generated.mines {
+            Ok(mines) => {
+                board.place_generated_mines(
+                    pending.action.coordinates(),
+                    &mines,
+                    generated.rng,
+                    board_settings,
+                );
+                Some(pending.action)
+            }
+            Err(error) => {
+                log::error!("Failed to generate board: {}", error);
+                commands.insert_resource(MenuError(error.to_string()));
+                None
             }
+        }
+    }
 
-            log::info!("Regenerating board for safe start...");
-            let bomb_count = board.tile_map.bomb_count;
-            board.tile_map = TileMap::empty(board.tile_map.width, board.tile_map.height);
-            board.tile_map.set_bombs(bomb_count);
+    /// Applies the reveal that started a board without guesses once its mines are placed
+    pub fn finish_pending_board(
+        mut commands: Commands,
+        mut pending: ResMut<PendingBoard>,
+        mut board: Single<&mut Board>,
+        board_settings: Res<BoardSettings>,
+        texts: Query<Entity, With<PendingBoardText>>,
+        mut board_changed_event: EventWriter<BoardChanged>,
+    ) {
+        if let Some(action) = Self::place_pending_mines(
+            &mut commands,
+            &mut pending,
+            &mut board,
+            &board_settings,
+            &texts,
+        ) {
+            Self::apply_action(
+                &mut commands,
+                &mut board,
+                action,
+                &board_settings,
+                &mut board_changed_event,
+            );
         }
     }
 
-    pub fn victory_validation(board: Single<&Board>, mut next_state: ResMut<NextState<AppState>>) {
+    /// Drops a board still being generated when leaving the game or replay, which cancels its
+    /// task
+    pub fn cancel_pending_board(
+        mut commands: Commands,
+        texts: Query<Entity, With<PendingBoardText>>,
+    ) {
+        commands.remove_resource::<PendingBoard>();
+        for entity in &texts {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    /// Returns to the main menu to show an error raised while playing
+    pub fn leave_on_error(mut next_state: ResMut<NextState<AppState>>) {
+        next_state.set(AppState::MainMenu);
+    }
+
+    pub fn victory_validation(
+        mut board: Single<&mut Board>,
+        mut next_state: ResMut<NextState<AppState>>,
+        mut board_changed_event: EventWriter<BoardChanged>,
+    ) {
         if board.tile_map.has_won() {
+            let flagged = board.flag_on_victory();
+            board_changed_event.write(BoardChanged {
+                coordinates: flagged,
+            });
             next_state.set(AppState::Victory);
         }
     }
 
-    pub fn right_click_tile(
+    /// Picks the action for the mouse buttons pressed this frame on a tile. A single action is
+    /// made per frame, so pressing both buttons together chords once whichever system order or
+    /// button arrives first.
+    pub fn click_action(
+        mouse_input: &ButtonInput<MouseButton>,
+        chord_trigger: ChordTrigger,
+        tile_state: TileState,
+        coords: Coordinates,
+    ) -> Option<GameAction> {
+        let left = mouse_input.just_pressed(MouseButton::Left);
+        let right = mouse_input.just_pressed(MouseButton::Right);
+        let both_buttons = chord_trigger == ChordTrigger::BothButtons
+            && ((left && mouse_input.pressed(MouseButton::Right))
+                || (right && mouse_input.pressed(MouseButton::Left)));
+
+        if both_buttons
+            || (chord_trigger == ChordTrigger::MiddleClick
+                && mouse_input.just_pressed(MouseButton::Middle))
+        {
+            Some(GameAction::Chord(coords))
+        } else if left {
+            if tile_state == TileState::Revealed && chord_trigger == ChordTrigger::LeftClick {
+                Some(GameAction::Chord(coords))
+            } else {
+                Some(GameAction::Reveal(coords))
+            }
+        } else if right {
+            Some(GameAction::ToggleFlag(coords))
+        } else {
+            None
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn click_tile(
         mouse_input: Res<ButtonInput<MouseButton>>,
         windows: Query<&Window>,
         mut camera: Query<(&Camera, &GlobalTransform)>,
-        mut tiles: Query<(Entity, &GlobalTransform, &Coordinates)>,
+        board_transform: Single<&GlobalTransform, With<Board>>,
         mut board: Single<&mut Board>,
         ui_settings: Res<UiSettings>,
+        board_settings: Res<BoardSettings>,
         mut board_changed_event: EventWriter<BoardChanged>,
+        mut commands: Commands,
     ) {
-        if !mouse_input.just_pressed(MouseButton::Right) {
+        if !mouse_input.any_just_pressed([
+            MouseButton::Left,
+            MouseButton::Right,
+            MouseButton::Middle,
+        ]) {
             return;
         }
 
         let Some(coords) = Self::get_tile_coords(
             windows,
             camera.transmute_lens(),
-            tiles.transmute_lens(),
             &board,
+            &board_transform,
             &ui_settings,
         ) else {
             return;
         };
+        let Some(tile) = board.tile_map.get(&coords) else {
+            return;
+        };
+        let Some(action) =
+            Self::click_action(&mouse_input, ui_settings.chord_trigger, tile.state, coords)
+        else {
+            return;
+        };
 
-        if let Some(tile) = board.tile_map.at_mut(&coords) {
-            if tile.state == TileState::Revealed || tile.state == TileState::Exploded {
-                return;
-            }
-
-            tile.toggle_flag();
-            board_changed_event.write(BoardChanged);
-        }
+        Self::apply_action(
+            &mut commands,
+            &mut board,
+            action,
+            &board_settings,
+            &mut board_changed_event,
+        );
     }
 
-    pub fn left_click_tile(
-        mouse_input: Res<ButtonInput<MouseButton>>,
-        windows: Query<&Window>,
-        mut camera: Query<(&Camera, &GlobalTransform)>,
-        mut tiles: Query<(Entity, &GlobalTransform, &Coordinates)>,
+    /// Ctrl+Z undoes the last action and Ctrl+Y redoes it. Undoing the losing move from the
+    /// defeat screen resumes the game.
+    pub fn undo_redo(
+        keys: Res<ButtonInput<KeyCode>>,
         mut board: Single<&mut Board>,
-        ui_settings: Res<crate::UiSettings>,
+        state: Res<State<AppState>>,
+        mut next_state: ResMut<NextState<AppState>>,
         mut board_changed_event: EventWriter<BoardChanged>,
     ) {
-        if !mouse_input.just_pressed(MouseButton::Left) {
+        if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
             return;
         }
 
-        let Some(coords) = Self::get_tile_coords(
-            windows,
-            camera.transmute_lens(),
-            tiles.transmute_lens(),
-            &board,
-            &ui_settings,
-        ) else {
+        let changed = if keys.just_pressed(KeyCode::KeyZ) {
+            board.undo()
+        } else if keys.just_pressed(KeyCode::KeyY) {
+            board.redo()
+        } else {
             return;
         };
 
-        if let Some(tile) = board.tile_map.at_mut(&coords) {
-            if tile.state == TileState::Flagged || tile.state == TileState::Exploded {
-                return;
-            }
-
-            board_changed_event.write(BoardChanged);
-            if tile.state == TileState::Revealed {
-                let _ = board.tile_map.reveal_neighbors(coords);
-                return;
-            }
-
-            tile.reveal();
+        let Some(changed) = changed else {
+            return;
+        };
+        board_changed_event.write(BoardChanged {
+            coordinates: changed,
+        });
 
-            match tile.r#type {
-                TileType::Empty => {
-                    board
-                        .tile_map
-                        .reveal_empty_neighbors(coords, &mut Vec::new());
-                }
-                _ => {}
-            }
+        if *state.get() == AppState::Defeat && !board.tile_map.has_lost() {
+            log::info!("Resuming game after undoing defeat");
+            next_state.set(AppState::InGame);
         }
     }
 
@@ -205,95 +355,102 @@ impl BoardPlugin {
     pub fn defeat_validation(
         mut board: Single<&mut Board>,
         mut next_state: ResMut<NextState<AppState>>,
+        mut board_changed_event: EventWriter<BoardChanged>,
     ) {
         if board.tile_map.has_lost() {
-            board.tile_map.reveal_all(false);
+            let revealed = board.reveal_on_defeat();
+            board_changed_event.write(BoardChanged {
+                coordinates: revealed,
+            });
             next_state.set(AppState::Defeat);
         }
     }
 
+    /// Redraws the tiles listed by `BoardChanged` events. Every tile keeps a single foreground
+    /// entity whose sprite or text is swapped, so nothing piles up across updates.
     pub fn update_board(
-        board: Single<&mut Board>,
-        tile_background: Query<
-            (Entity, &GlobalTransform, &Coordinates, &Children),
-            With<TileImageState>,
-        >,
-        mut tile_foregrounds: Query<Entity, With<TileImageMarker>>,
+        board: Single<&Board>,
         ui_settings: Res<UiSettings>,
         mut commands: Commands,
         sprites: Res<Sprites>,
         mut change_reader: EventReader<BoardChanged>,
     ) {
-        if change_reader.is_empty() {
-            return;
+        let game_lost = board.tile_map.has_lost();
+        for changed in change_reader.read() {
+            log::info!("Updating {} tiles", changed.coordinates.len());
+
+            for coords in &changed.coordinates {
+                let (Some(tile), Some(entities)) =
+                    (board.tile_map.get(coords), board.tile_entities.get(coords))
+                else {
+                    continue;
+                };
+
+                Self::draw_tile(
+                    &mut commands,
+                    TileVisual::of(tile, game_lost),
+                    entities,
+                    &sprites,
+                    &ui_settings,
+                );
+            }
         }
-        change_reader.clear();
-        log::info!("Updating board visuals...");
+    }
 
+    fn draw_tile(
+        commands: &mut Commands,
+        visual: TileVisual,
+        entities: &TileEntities,
+        sprites: &Sprites,
+        ui_settings: &UiSettings,
+    ) {
         let box_size = Vec2::new(ui_settings.tile_size, ui_settings.tile_size);
+        let sprite = |image: &Handle<Image>| Sprite {
+            custom_size: Some(box_size),
+            image: image.clone(),
+            ..Default::default()
+        };
+
+        let background = match visual {
+            TileVisual::TriggeredMine => &sprites.triggered,
+            visual if visual.is_uncovered() => &sprites.uncovered,
+            _ => &sprites.cover,
+        };
+        commands
+            .entity(entities.background)
+            .insert(sprite(background));
+
+        let mut foreground = commands.entity(entities.foreground);
+        let image = match visual {
+            TileVisual::Covered | TileVisual::Empty => None,
+            TileVisual::Flag => Some(&sprites.flag),
+            TileVisual::Question => Some(&sprites.question),
+            TileVisual::TriggeredMine => Some(&sprites.explosion),
+            TileVisual::Mine => Some(&sprites.bomb),
+            TileVisual::WrongFlag => Some(&sprites.wrong_flag),
+            TileVisual::Number(n) => {
+                foreground.remove::<Sprite>().insert((
+                    Text2d::new(n.to_string()),
+                    TextColor(match n {
+                        1 => BLUE.into(),
+                        2 => GREEN.into(),
+                        3 => ORANGE.into(),
+                        _ => RED.into(),
+                    }),
+                    TextFont::from_font(sprites.font.clone()).with_font_size(24.0),
+                    TextLayout::new_with_justify(JustifyText::Center),
+                ));
+                return;
+            }
+        };
 
-        for (image_state_entity, _, coords, children) in &tile_background {
-            if let Some(tile) = board.tile_map.at(coords) {
-                let image_marker_entity = tile_foregrounds
-                    .get_mut(children[0])
-                    .expect("Failed to get tile top sprite");
-
-                match tile.state {
-                    TileState::Hidden => {
-                        commands.entity(image_state_entity).insert(Sprite {
-                            custom_size: Some(box_size),
-                            image: sprites.cover.clone(),
-                            ..Default::default()
-                        });
-
-                        commands.entity(image_marker_entity).remove::<Sprite>();
-                    }
-                    TileState::Flagged => {
-                        commands.entity(image_marker_entity).insert(Sprite {
-                            custom_size: Some(box_size),
-                            image: sprites.flag.clone(),
-                            ..Default::default()
-                        });
-                    }
-                    TileState::Exploded => {
-                        commands.entity(image_marker_entity).insert(Sprite {
-                            custom_size: Some(box_size),
-                            image: sprites.explosion.clone(),
-                            ..Default::default()
-                        });
-                    }
-                    TileState::Revealed => {
-                        commands.entity(image_state_entity).insert(Sprite {
-                            custom_size: Some(box_size),
-                            image: sprites.uncovered.clone(),
-                            ..Default::default()
-                        });
-
-                        match tile.r#type {
-                            TileType::Bomb => {
-                                commands.entity(image_marker_entity).insert(Sprite {
-                                    custom_size: Some(box_size),
-                                    image: sprites.bomb.clone(),
-                                    ..Default::default()
-                                });
-                            }
-                            TileType::Neighbour(n) => {
-                                commands.entity(image_marker_entity).with_child((
-                                    Text2d::new(n.to_string()),
-                                    TextColor(match n {
-                                        1 => BLUE.into(),
-                                        2 => GREEN.into(),
-                                        3 => ORANGE.into(),
-                                        _ => RED.into(),
-                                    }),
-                                    TextFont::from_font(sprites.font.clone()).with_font_size(24.0),
-                                    TextLayout::new_with_justify(JustifyText::Center),
-                                ));
-                            }
-                            TileType::Empty => {}
-                        }
-                    }
-                }
+        foreground.remove::<(Text2d, TextColor, TextFont, TextLayout)>();
+        match image {
+            Some(image) => {
+                foreground.insert(sprite(image));
+            }
+            None => {
+                foreground.remove::<Sprite>();
             }
         }
     }
@@ -303,85 +460,120 @@ impl BoardPlugin {
         asset_server: ResMut<AssetServer>,
         ui_settings: Res<UiSettings>,
         board_settings: Res<BoardSettings>,
+        preset: Option<Res<PresetBoard>>,
+        mut next_state: ResMut<NextState<AppState>>,
+        mut board_changed_event: EventWriter<BoardChanged>,
     ) {
+        if let Err(error) = board_settings.validate() {
+            log::error!("Invalid board settings: {}", error);
+            commands.insert_resource(MenuError(error.to_string()));
+            next_state.set(AppState::MainMenu);
+            return;
+        }
+
         let explosion: Handle<Image> = asset_server.load("icons/explosion.png");
         let flag: Handle<Image> = asset_server.load("icons/flag.png");
+        let question: Handle<Image> = asset_server.load("icons/question.png");
         let uncovered: Handle<Image> = asset_server.load("icons/uncovered.png");
         let bomb: Handle<Image> = asset_server.load("icons/bomb.png");
+        let wrong_flag: Handle<Image> = asset_server.load("icons/wrong_flag.png");
+        let triggered: Handle<Image> = asset_server.load("icons/triggered.png");
         let font: Handle<Font> = asset_server.load("fonts/ChakraPetch-Regular.ttf");
         let cover: Handle<Image> = asset_server.load("icons/cover.png");
 
         commands.insert_resource(Sprites {
             explosion: explosion.clone(),
             flag: flag.clone(),
+            question: question.clone(),
             cover: cover.clone(),
             uncovered: uncovered.clone(),
             bomb: bomb.clone(),
+            wrong_flag: wrong_flag.clone(),
+            triggered: triggered.clone(),
             font: font.clone(),
         });
 
-        let mut tile_map = TileMap::empty(board_settings.board_width, board_settings.board_height);
-        tile_map.set_bombs(board_settings.mine_count);
-        log::info!("{}", tile_map.console_output());
+        let mut board = Board::new(&board_settings);
+        let preset_tiles = preset.map(|preset| {
+            commands.remove_resource::<PresetBoard>();
+            board.load_preset(preset.0.clone())
+        });
         log::info!(
-            "Board size: {}x{}, Bombs: {}",
+            "Board size: {}x{}, Bombs: {}, Seed: {}",
             board_settings.board_width,
             board_settings.board_height,
-            board_settings.mine_count
+            board_settings.mine_count,
+            board_settings.seed
         );
         log::info!("Tile size: {}", ui_settings.tile_size);
 
-        commands
+        let board_entity = commands
             .spawn((
                 Sprite::from_color(Color::WHITE, Vec2::ONE),
                 Transform {
                     translation: Vec3::new(
-                        -(tile_map.width as f32
+                        -(board.tile_map.width() as f32
                             * (ui_settings.tile_size + ui_settings.tile_spacing))
                             / 2.0,
-                        -(tile_map.height as f32
+                        -(board.tile_map.height() as f32
                             * (ui_settings.tile_size + ui_settings.tile_spacing))
                             / 2.0,
                         0.0,
                     ),
                     ..Default::default()
                 },
-                Board {
-                    tile_map: tile_map.clone(),
-                },
             ))
-            .with_children(|commands| {
-                for y in 0..tile_map.height {
-                    for x in 0..tile_map.width {
-                        let position = Vec3::new(
-                            x as f32 * (ui_settings.tile_size + ui_settings.tile_spacing),
-                            y as f32 * (ui_settings.tile_size + ui_settings.tile_spacing),
-                            0.0,
-                        );
-
-                        let box_size = Vec2::new(ui_settings.tile_size, ui_settings.tile_size);
-                        commands
-                            .spawn((
-                                Sprite {
-                                    custom_size: Some(box_size),
-                                    image: cover.clone(),
-                                    ..Default::default()
-                                },
-                                TileImageState,
-                                Transform::from_translation(position),
-                                Name::new(format!("Tile ({}, {})", x, y)),
-                                Coordinates { x, y },
-                            ))
-                            .with_children(|commands| {
-                                commands.spawn((
-                                    TileImageMarker,
-                                    Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
-                                    Visibility::default(),
-                                ));
-                            });
-                    }
-                }
-            });
+            .id();
+
+        let box_size = Vec2::new(ui_settings.tile_size, ui_settings.tile_size);
+        for coords in board.tile_map.coordinates() {
+            let position = Vec3::new(
+                coords.x as f32 * (ui_settings.tile_size + ui_settings.tile_spacing),
+                coords.y as f32 * (ui_settings.tile_size + ui_settings.tile_spacing),
+                0.0,
+            );
+
+            let background = commands
+                .spawn((
+                    Sprite {
+                        custom_size: Some(box_size),
+                        image: cover.clone(),
+                        ..Default::default()
+                    },
+                    TileImageState,
+                    Transform::from_translation(position),
+                    Name::new(format!("Tile {}", coords)),
+                    coords,
+                    ChildOf(board_entity),
+                ))
+                .id();
+            let foreground = commands
+                .spawn((
+                    TileImageMarker,
+                    Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
+                    Visibility::default(),
+                    ChildOf(background),
+                ))
+                .id();
+
+            board.tile_entities.insert(
+                coords,
+                TileEntities {
+                    background,
+                    foreground,
+                },
+            );
+        }
+
+        commands.entity(board_entity).insert(board);
+        if let Some(coordinates) = preset_tiles {
+            board_changed_event.write(BoardChanged { coordinates });
+        }
+    }
+
+    /// Advances the game clock while the board is being played
+    pub fn tick_clock(time: Res<Time>, mut board: Single<&mut Board>) {
+        board.elapsed += time.delta();
     }
 }
 
@@ -390,3 +582,83 @@ pub struct TileImageState;
 
 #[derive(Component)]
 pub struct TileImageMarker;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TILE: Coordinates = Coordinates { x: 1, y: 1 };
+
+    fn actions_over_frames(
+        frames: &[&[MouseButton]],
+        chord_trigger: ChordTrigger,
+        tile_state: TileState,
+    ) -> Vec<GameAction> {
+        let mut mouse_input = ButtonInput::<MouseButton>::default();
+        let mut actions = Vec::new();
+        for pressed in frames {
+            mouse_input.clear();
+            for button in *pressed {
+                mouse_input.press(*button);
+            }
+            actions.extend(BoardPlugin::click_action(
+                &mouse_input,
+                chord_trigger,
+                tile_state,
+                TILE,
+            ));
+        }
+        actions
+    }
+
+    #[test]
+    fn both_buttons_in_one_frame_chord_once() {
+        let actions = actions_over_frames(
+            &[&[MouseButton::Left, MouseButton::Right]],
+            ChordTrigger::BothButtons,
+            TileState::Revealed,
+        );
+        assert_eq!(actions, vec![GameAction::Chord(TILE)]);
+
+        // Holding the buttons does not chord again
+        let actions = actions_over_frames(
+            &[
+                &[MouseButton::Right, MouseButton::Left],
+                &[MouseButton::Right, MouseButton::Left],
+            ],
+            ChordTrigger::BothButtons,
+            TileState::Revealed,
+        );
+        assert_eq!(actions, vec![GameAction::Chord(TILE)]);
+    }
+
+    #[test]
+    fn buttons_pressed_one_after_the_other() {
+        let actions = actions_over_frames(
+            &[
+                &[MouseButton::Left],
+                &[MouseButton::Left, MouseButton::Right],
+            ],
+            ChordTrigger::BothButtons,
+            TileState::Revealed,
+        );
+        assert_eq!(
+            actions,
+            vec![GameAction::Reveal(TILE), GameAction::Chord(TILE)]
+        );
+
+        let actions = actions_over_frames(
+            &[
+                &[MouseButton::Left],
+                &[MouseButton::Right],
+                &[MouseButton::Middle],
+            ],
+            ChordTrigger::LeftClick,
+            TileState::Hidden,
+        );
+        assert_eq!(
+            actions,
+            vec![GameAction::Reveal(TILE), GameAction::ToggleFlag(TILE)]
+        );
+    }
+}
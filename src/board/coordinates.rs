@@ -3,9 +3,12 @@ use std::{
     ops::{Add, Sub},
 };
 
+#[cfg(feature = "bevy")]
 use bevy::ecs::component::Component;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "bevy", derive(Component))]
 pub struct Coordinates {
     pub x: u16,
     pub y: u16,
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use crate::board::coordinates::Coordinates;
+
+/// A move on the board, independent of the input that produced it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameAction {
+    /// Reveals a hidden tile, flood filling the opening around it
+    Reveal(Coordinates),
+    /// Places or removes a flag on a hidden tile
+    ToggleFlag(Coordinates),
+    /// Reveals the hidden neighbors of a revealed number
+    Chord(Coordinates),
+}
+
+impl GameAction {
+    pub fn coordinates(&self) -> Coordinates {
+        match self {
+            GameAction::Reveal(coordinates)
+            | GameAction::ToggleFlag(coordinates)
+            | GameAction::Chord(coordinates) => *coordinates,
+        }
+    }
+}
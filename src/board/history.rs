@@ -0,0 +1,74 @@
+use crate::board::{
+    coordinates::Coordinates, game_action::GameAction, tile_change::TileChange, tile_map::TileMap,
+};
+
+/// An applied action together with every tile it changed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub action: GameAction,
+    pub changes: Vec<TileChange>,
+}
+
+/// Undo and redo stacks of the actions applied to a board
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct History {
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// Records an applied action, dropping the actions that could be redone. Actions that did not
+    /// change the board are ignored.
+    pub fn record(&mut self, action: GameAction, changes: Vec<TileChange>) {
+        if changes.is_empty() {
+            return;
+        }
+
+        self.undo.push(HistoryEntry { action, changes });
+        self.redo.clear();
+    }
+
+    /// Adds changes that followed from the last action, such as the board revealed on defeat,
+    /// so they are undone together with it
+    pub fn extend_last(&mut self, changes: Vec<TileChange>) {
+        if let Some(entry) = self.undo.last_mut() {
+            entry.changes.extend(changes);
+        }
+    }
+
+    /// Reverts the last action, returning the tiles it changed
+    pub fn undo(&mut self, tile_map: &mut TileMap) -> Option<Vec<Coordinates>> {
+        let entry = self.undo.pop()?;
+        tile_map.revert(&entry.changes);
+        let changed = entry
+            .changes
+            .iter()
+            .map(|change| change.coordinates)
+            .collect();
+        self.redo.push(entry);
+
+        Some(changed)
+    }
+
+    /// Applies the last undone action again, returning the tiles it changed
+    pub fn redo(&mut self, tile_map: &mut TileMap) -> Option<Vec<Coordinates>> {
+        let entry = self.redo.pop()?;
+        tile_map.reapply(&entry.changes);
+        let changed = entry
+            .changes
+            .iter()
+            .map(|change| change.coordinates)
+            .collect();
+        self.undo.push(entry);
+
+        Some(changed)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
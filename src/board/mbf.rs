@@ -0,0 +1,148 @@
+//! Binary MBF layout shared by other Minesweeper tools. A file holds the width and height on one
+//! byte each, the mine count as a big endian `u16`, then the `x` and `y` byte of every mine with
+//! `y = 0` being the top row. Only the mines are stored, every tile is loaded hidden.
+
+use std::collections::HashSet;
+
+use crate::board::{coordinates::Coordinates, mbf_error::MbfError, tile_map::TileMap};
+
+const HEADER_LEN: usize = 4;
+
+impl TileMap {
+    /// Reads a mine layout in the MBF layout
+    pub fn from_mbf(bytes: &[u8]) -> Result<Self, MbfError> {
+        let Some(header) = bytes.get(..HEADER_LEN) else {
+            return Err(MbfError::Truncated {
+                expected: HEADER_LEN,
+                found: bytes.len(),
+            });
+        };
+        let width = header[0] as u16;
+        let height = header[1] as u16;
+        let mine_count = u16::from_be_bytes([header[2], header[3]]);
+
+        if width == 0 || height == 0 {
+            return Err(MbfError::ZeroDimension { width, height });
+        }
+
+        let tiles = width as u32 * height as u32;
+        if mine_count as u32 > tiles {
+            return Err(MbfError::TooManyMines { mine_count, tiles });
+        }
+
+        let expected = HEADER_LEN + 2 * mine_count as usize;
+        if bytes.len() < expected {
+            return Err(MbfError::Truncated {
+                expected,
+                found: bytes.len(),
+            });
+        }
+        if bytes.len() > expected {
+            return Err(MbfError::TrailingBytes {
+                expected,
+                found: bytes.len(),
+            });
+        }
+
+        let mut mines = HashSet::with_capacity(mine_count as usize);
+        for pair in bytes[HEADER_LEN..].chunks_exact(2) {
+            let (x, y) = (pair[0], pair[1]);
+            if x as u16 >= width || y as u16 >= height {
+                return Err(MbfError::MineOutOfBounds { x, y });
+            }
+            if !mines.insert(Coordinates {
+                x: x as u16,
+                y: height - 1 - y as u16,
+            }) {
+                return Err(MbfError::DuplicateMine { x, y });
+            }
+        }
+
+        let mines: Vec<Coordinates> = mines.into_iter().collect();
+        Ok(Self::with_mines(width, height, &mines))
+    }
+
+    /// Writes the mine layout in the MBF layout, the state of the tiles is not kept
+    pub fn to_mbf(&self) -> Result<Vec<u8>, MbfError> {
+        let (width, height) = (self.width(), self.height());
+        if width > u8::MAX as u16 || height > u8::MAX as u16 {
+            return Err(MbfError::TooLarge { width, height });
+        }
+
+        let mines: Vec<Coordinates> = self
+            .coordinates()
+            .filter(|coords| self.is_bomb_at(*coords))
+            .collect();
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + 2 * mines.len());
+        bytes.push(width as u8);
+        bytes.push(height as u8);
+        bytes.extend_from_slice(&(mines.len() as u16).to_be_bytes());
+        for coords in mines {
+            bytes.push(coords.x as u8);
+            bytes.push((height - 1 - coords.y) as u8);
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mbf_round_trip() {
+        let tile_map: TileMap = "3x2\n*..\n..*\n".parse().unwrap();
+
+        let bytes = tile_map.to_mbf().unwrap();
+        assert_eq!(bytes, [3, 2, 0, 2, 2, 1, 0, 0]);
+        assert_eq!(TileMap::from_mbf(&bytes).unwrap(), tile_map);
+    }
+
+    #[test]
+    fn mbf_rejects_inconsistent_files() {
+        assert_eq!(
+            TileMap::from_mbf(&[3, 2]),
+            Err(MbfError::Truncated {
+                expected: 4,
+                found: 2
+            })
+        );
+        assert_eq!(
+            TileMap::from_mbf(&[3, 2, 0, 2, 0, 0]),
+            Err(MbfError::Truncated {
+                expected: 8,
+                found: 6
+            })
+        );
+        assert_eq!(
+            TileMap::from_mbf(&[3, 2, 0, 1, 0, 0, 1]),
+            Err(MbfError::TrailingBytes {
+                expected: 6,
+                found: 7
+            })
+        );
+        assert_eq!(
+            TileMap::from_mbf(&[3, 2, 0, 7]),
+            Err(MbfError::TooManyMines {
+                mine_count: 7,
+                tiles: 6
+            })
+        );
+        assert_eq!(
+            TileMap::from_mbf(&[3, 2, 0, 1, 3, 0]),
+            Err(MbfError::MineOutOfBounds { x: 3, y: 0 })
+        );
+        assert_eq!(
+            TileMap::from_mbf(&[3, 2, 0, 2, 1, 1, 1, 1]),
+            Err(MbfError::DuplicateMine { x: 1, y: 1 })
+        );
+        assert_eq!(
+            TileMap::from_mbf(&[0, 2, 0, 0]),
+            Err(MbfError::ZeroDimension {
+                width: 0,
+                height: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// Error reading or writing a board in the MBF layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MbfError {
+    /// The file ends before the header or the mine list is complete
+    Truncated {
+        expected: usize,
+        found: usize,
+    },
+    /// Bytes remain after the mine list
+    TrailingBytes {
+        expected: usize,
+        found: usize,
+    },
+    ZeroDimension {
+        width: u16,
+        height: u16,
+    },
+    TooManyMines {
+        mine_count: u16,
+        tiles: u32,
+    },
+    MineOutOfBounds {
+        x: u8,
+        y: u8,
+    },
+    DuplicateMine {
+        x: u8,
+        y: u8,
+    },
+    /// MBF stores sizes on a single byte
+    TooLarge {
+        width: u16,
+        height: u16,
+    },
+}
+
+impl Display for MbfError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MbfError::Truncated { expected, found } => write!(
+                f,
+                "MBF file is truncated: expected {} bytes, found {}",
+                expected, found
+            ),
+            MbfError::TrailingBytes { expected, found } => write!(
+                f,
+                "MBF file has trailing data: expected {} bytes, found {}",
+                expected, found
+            ),
+            MbfError::ZeroDimension { width, height } => {
+                write!(f, "MBF board has a zero dimension: {}x{}", width, height)
+            }
+            MbfError::TooManyMines { mine_count, tiles } => write!(
+                f,
+                "MBF board has {} mines but only {} tiles",
+                mine_count, tiles
+            ),
+            MbfError::MineOutOfBounds { x, y } => {
+                write!(f, "MBF mine at ({}, {}) is outside the board", x, y)
+            }
+            MbfError::DuplicateMine { x, y } => {
+                write!(f, "MBF mine at ({}, {}) is listed twice", x, y)
+            }
+            MbfError::TooLarge { width, height } => write!(
+                f,
+                "Board {}x{} is too large for MBF, sizes are limited to 255",
+                width, height
+            ),
+        }
+    }
+}
+
+impl Error for MbfError {}
@@ -1,8 +1,32 @@
+pub mod action_counters;
+pub mod action_outcome;
+#[cfg(feature = "bevy")]
+#[allow(clippy::module_inception)]
 pub mod board;
-mod board_changed;
+#[cfg(feature = "bevy")]
+pub mod board_changed;
+#[cfg(feature = "bevy")]
 pub mod board_plugin;
 pub mod coordinates;
+pub mod game_action;
+pub mod history;
+pub mod mbf;
+pub mod mbf_error;
+pub mod no_guess_error;
+#[cfg(feature = "bevy")]
+pub mod pending_board;
+#[cfg(feature = "bevy")]
+pub mod preset_board;
+#[cfg(feature = "bevy")]
+pub mod preset_board_error;
+pub mod rules;
 pub mod settings;
+pub mod settings_error;
+#[cfg(feature = "bevy")]
 pub mod sprites;
+pub mod text_format;
+pub mod text_format_error;
 pub mod tile;
+pub mod tile_change;
+pub mod tile_counts;
 pub mod tile_map;
@@ -0,0 +1,27 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// No board that can be solved without guessing was found within the retry budget, usually
+/// because the board holds too many mines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoGuessError {
+    pub attempts: u32,
+    pub width: u16,
+    pub height: u16,
+    pub mine_count: u16,
+}
+
+impl Display for NoGuessError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "No {}x{} board with {} mines could be solved without guessing after {} attempts, \
+             try fewer mines",
+            self.width, self.height, self.mine_count, self.attempts
+        )
+    }
+}
+
+impl Error for NoGuessError {}
@@ -0,0 +1,15 @@
+use bevy::{prelude::*, tasks::Task};
+
+use crate::board::{board::GeneratedMines, game_action::GameAction};
+
+/// First reveal of a board without guesses waiting for its mines, generated off the frame.
+/// Input on the board is ignored until the task finishes.
+#[derive(Resource)]
+pub struct PendingBoard {
+    pub action: GameAction,
+    pub task: Task<GeneratedMines>,
+}
+
+/// Text shown while a board without guesses is being generated
+#[derive(Component)]
+pub struct PendingBoardText;
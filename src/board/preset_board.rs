@@ -0,0 +1,46 @@
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+
+use crate::board::{
+    preset_board_error::PresetBoardError,
+    settings::{BoardSettings, FirstClickPolicy},
+    settings_error::BoardSettingsError,
+    tile_map::TileMap,
+};
+
+/// Board loaded from a file, used by the next `BoardPlugin::create_board` instead of placing
+/// random mines
+#[derive(Resource, Clone, Debug)]
+pub struct PresetBoard(pub TileMap);
+
+impl PresetBoard {
+    /// Reads a board file, `.mbf` files are read as MBF layouts and anything else in the text
+    /// format, see `board::mbf` and `board::text_format`
+    pub fn load(path: &Path) -> Result<Self, PresetBoardError> {
+        let is_mbf = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("mbf"));
+        if is_mbf {
+            Ok(Self(TileMap::from_mbf(&fs::read(path)?)?))
+        } else {
+            Ok(Self(fs::read_to_string(path)?.parse()?))
+        }
+    }
+
+    /// Settings describing the preset, the mines are already placed so the first click is not
+    /// protected
+    pub fn settings(&self) -> Result<BoardSettings, BoardSettingsError> {
+        let settings = BoardSettings {
+            board_width: self.0.width(),
+            board_height: self.0.height(),
+            mine_count: self.0.bomb_count,
+            seed: 0,
+            first_click: FirstClickPolicy::NoProtection,
+            rules: self.0.rules,
+            no_guess: false,
+        };
+        settings.validate()?;
+        Ok(settings)
+    }
+}
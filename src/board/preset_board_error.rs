@@ -0,0 +1,64 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+use crate::board::{
+    mbf_error::MbfError, settings_error::BoardSettingsError, text_format_error::TextFormatError,
+};
+
+#[derive(Debug)]
+pub enum PresetBoardError {
+    Io(io::Error),
+    Text(TextFormatError),
+    Mbf(MbfError),
+    /// The board can not be played with the current rules
+    Settings(BoardSettingsError),
+}
+
+impl Display for PresetBoardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PresetBoardError::Io(error) => write!(f, "Could not read board file: {}", error),
+            PresetBoardError::Text(error) => write!(f, "Invalid board file: {}", error),
+            PresetBoardError::Mbf(error) => write!(f, "Invalid board file: {}", error),
+            PresetBoardError::Settings(error) => write!(f, "Invalid board: {}", error),
+        }
+    }
+}
+
+impl Error for PresetBoardError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PresetBoardError::Io(error) => Some(error),
+            PresetBoardError::Text(error) => Some(error),
+            PresetBoardError::Mbf(error) => Some(error),
+            PresetBoardError::Settings(error) => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for PresetBoardError {
+    fn from(error: io::Error) -> Self {
+        PresetBoardError::Io(error)
+    }
+}
+
+impl From<TextFormatError> for PresetBoardError {
+    fn from(error: TextFormatError) -> Self {
+        PresetBoardError::Text(error)
+    }
+}
+
+impl From<MbfError> for PresetBoardError {
+    fn from(error: MbfError) -> Self {
+        PresetBoardError::Mbf(error)
+    }
+}
+
+impl From<BoardSettingsError> for PresetBoardError {
+    fn from(error: BoardSettingsError) -> Self {
+        PresetBoardError::Settings(error)
+    }
+}
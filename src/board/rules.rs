@@ -0,0 +1,13 @@
+#[cfg(feature = "bevy")]
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Optional rules changing how a board is played
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bevy", derive(Resource))]
+pub struct GameRules {
+    /// Marking a flagged tile again turns the flag into a question mark instead of removing it
+    pub question_marks: bool,
+    /// The game is only won once every mine is flagged, on top of every safe tile revealed
+    pub strict_flags: bool,
+}
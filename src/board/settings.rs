@@ -1,8 +1,189 @@
+#[cfg(feature = "bevy")]
 use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Resource)]
+use crate::board::{
+    coordinates::Coordinates, rules::GameRules, settings_error::BoardSettingsError,
+    tile_map::TileMap,
+};
+
+/// Largest width or height of a board, neighbor offsets are computed on `i16` coordinates
+pub const MAX_SIDE: u32 = i16::MAX as u32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bevy", derive(Resource))]
 pub struct BoardSettings {
     pub board_width: u16,
     pub board_height: u16,
     pub mine_count: u16,
+    /// Seed for the board generator, the same seed and first click always produce the same layout
+    pub seed: u64,
+    /// Protection given to the first revealed tile when the mines are placed
+    pub first_click: FirstClickPolicy,
+    #[serde(default)]
+    pub rules: GameRules,
+    /// Only generate boards that can be cleared from the first click without guessing
+    #[serde(default)]
+    pub no_guess: bool,
+}
+
+impl BoardSettings {
+    /// Creates settings for a board with a random seed and the default first click policy,
+    /// rejecting boards that can not be generated
+    pub fn new(width: u32, height: u32, mine_count: u32) -> Result<Self, BoardSettingsError> {
+        if width == 0 || height == 0 {
+            return Err(BoardSettingsError::ZeroDimension { width, height });
+        }
+
+        if width > MAX_SIDE || height > MAX_SIDE {
+            return Err(BoardSettingsError::TooLarge { width, height });
+        }
+
+        let max = (width * height).min(u16::MAX as u32);
+        if mine_count > max {
+            return Err(BoardSettingsError::TooManyMines { mine_count, max });
+        }
+
+        let settings = Self {
+            board_width: width as u16,
+            board_height: height as u16,
+            mine_count: mine_count as u16,
+            seed: rand::rng().random(),
+            first_click: FirstClickPolicy::default(),
+            rules: GameRules::default(),
+            no_guess: false,
+        };
+        settings.validate()?;
+
+        Ok(settings)
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    pub fn with_rules(self, rules: GameRules) -> Self {
+        Self { rules, ..self }
+    }
+
+    pub fn with_no_guess(self, no_guess: bool) -> Self {
+        Self { no_guess, ..self }
+    }
+
+    pub fn with_first_click(
+        self,
+        first_click: FirstClickPolicy,
+    ) -> Result<Self, BoardSettingsError> {
+        let settings = Self {
+            first_click,
+            ..self
+        };
+        settings.validate()?;
+
+        Ok(settings)
+    }
+
+    /// Checks that a board can be generated from these settings, whatever tile is clicked first
+    pub fn validate(&self) -> Result<(), BoardSettingsError> {
+        let (width, height) = (self.board_width as u32, self.board_height as u32);
+        let mine_count = self.mine_count as u32;
+        if width == 0 || height == 0 {
+            return Err(BoardSettingsError::ZeroDimension { width, height });
+        }
+
+        if width > MAX_SIDE || height > MAX_SIDE {
+            return Err(BoardSettingsError::TooLarge { width, height });
+        }
+
+        let tile_count = width * height;
+        if mine_count > tile_count {
+            return Err(BoardSettingsError::TooManyMines {
+                mine_count,
+                max: tile_count.min(u16::MAX as u32),
+            });
+        }
+
+        let available = tile_count - self.first_click.max_excluded(width, height);
+        if mine_count > available {
+            return Err(BoardSettingsError::NoRoomForFirstClick {
+                mine_count,
+                available,
+                policy: self.first_click,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Decides which tiles around the first click are kept free of mines
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FirstClickPolicy {
+    /// Mines can be placed anywhere, including under the first click
+    NoProtection,
+    /// The first clicked tile is never a mine
+    SafeCell,
+    /// The first clicked tile and all its neighbors are never mines, so the first click always
+    /// opens an area
+    #[default]
+    GuaranteedOpening,
+}
+
+impl FirstClickPolicy {
+    /// Returns the tiles that may not hold a mine when the first click is at `coordinates`
+    pub fn excluded_coordinates(
+        &self,
+        tile_map: &TileMap,
+        coordinates: Coordinates,
+    ) -> Vec<Coordinates> {
+        match self {
+            FirstClickPolicy::NoProtection => Vec::new(),
+            FirstClickPolicy::SafeCell => vec![coordinates],
+            FirstClickPolicy::GuaranteedOpening => std::iter::once(coordinates)
+                .chain(tile_map.scan_map_at(coordinates))
+                .filter(|&coord| tile_map.coords_in_bounds(coord))
+                .collect(),
+        }
+    }
+
+    /// The largest number of tiles this policy can exclude on a board of the given size
+    pub fn max_excluded(&self, width: u32, height: u32) -> u32 {
+        match self {
+            FirstClickPolicy::NoProtection => 0,
+            FirstClickPolicy::SafeCell => 1,
+            FirstClickPolicy::GuaranteedOpening => width.min(3) * height.min(3),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_sides_beyond_neighbor_range() {
+        assert_eq!(
+            BoardSettings::new(MAX_SIDE + 1, 1, 0),
+            Err(BoardSettingsError::TooLarge {
+                width: MAX_SIDE + 1,
+                height: 1
+            })
+        );
+        assert!(BoardSettings::new(1, 40_000, 0).is_err());
+
+        let settings = BoardSettings::new(MAX_SIDE, 1, 0).unwrap();
+        let too_wide = BoardSettings {
+            board_width: 40_000,
+            ..settings.clone()
+        };
+        assert!(too_wide.validate().is_err());
+
+        let mut tile_map = TileMap::empty(settings.board_width, settings.board_height);
+        let edge = Coordinates {
+            x: settings.board_width - 1,
+            y: 0,
+        };
+        assert_eq!(tile_map.reveal_from(edge).len(), MAX_SIDE as usize);
+    }
 }
@@ -0,0 +1,54 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::board::settings::{FirstClickPolicy, MAX_SIDE};
+
+/// Reasons a board configuration can not be played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardSettingsError {
+    /// The board has no tiles
+    ZeroDimension { width: u32, height: u32 },
+    /// The board is wider or taller than `MAX_SIDE`
+    TooLarge { width: u32, height: u32 },
+    /// There are more mines than the board can hold
+    TooManyMines { mine_count: u32, max: u32 },
+    /// The mines do not fit next to the tiles kept free by the first click policy
+    NoRoomForFirstClick {
+        mine_count: u32,
+        available: u32,
+        policy: FirstClickPolicy,
+    },
+}
+
+impl Display for BoardSettingsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardSettingsError::ZeroDimension { width, height } => {
+                write!(f, "Board of {}x{} has no tiles", width, height)
+            }
+            BoardSettingsError::TooLarge { width, height } => write!(
+                f,
+                "Board of {}x{} is too large, width and height can be at most {}",
+                width, height, MAX_SIDE
+            ),
+            BoardSettingsError::TooManyMines { mine_count, max } => write!(
+                f,
+                "{} mines do not fit on the board, at most {} are allowed",
+                mine_count, max
+            ),
+            BoardSettingsError::NoRoomForFirstClick {
+                mine_count,
+                available,
+                policy,
+            } => write!(
+                f,
+                "{} mines leave no room for the first click ({:?}), at most {} are allowed",
+                mine_count, policy, available
+            ),
+        }
+    }
+}
+
+impl Error for BoardSettingsError {}
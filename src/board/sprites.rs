@@ -4,8 +4,13 @@ use bevy::prelude::*;
 pub struct Sprites {
     pub explosion: Handle<Image>,
     pub flag: Handle<Image>,
+    pub question: Handle<Image>,
     pub cover: Handle<Image>,
     pub uncovered: Handle<Image>,
     pub bomb: Handle<Image>,
+    /// Crossed out mine drawn over flags that were wrong
+    pub wrong_flag: Handle<Image>,
+    /// Background of the mine that lost the game
+    pub triggered: Handle<Image>,
     pub font: Handle<Font>,
 }
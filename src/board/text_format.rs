@@ -0,0 +1,281 @@
+//! Plain text format for boards, meant to be written by hand in test fixtures and bug reports.
+//!
+//! The first line is a `<width>x<height>` header, followed by one line per row from the top row
+//! (`y = height - 1`) down to the bottom row (`y = 0`), one character per tile:
+//!
+//! | Character | Tile                                       |
+//! |-----------|--------------------------------------------|
+//! | `.`       | hidden safe tile                           |
+//! | `*`       | hidden mine                                |
+//! | `f`       | flag on a safe tile                        |
+//! | `F`       | flag on a mine                             |
+//! | `q`       | question mark on a safe tile               |
+//! | `Q`       | question mark on a mine                    |
+//! | `0`-`8`   | revealed safe tile and its number of mines |
+//! | `@`       | revealed mine                              |
+//! | `X`       | exploded mine                              |
+//!
+//! Empty lines and lines starting with `#` are ignored. Revealed numbers must match the mines
+//! around them.
+//!
+//! ```text
+//! # a 1-2-1 pattern
+//! 3x2
+//! *.*
+//! 121
+//! ```
+
+use std::str::FromStr;
+
+use crate::board::{
+    coordinates::Coordinates,
+    settings::MAX_SIDE,
+    text_format_error::{TextFormatError, TextFormatErrorKind},
+    tile::{tile_state::TileState, tile_type::TileType},
+    tile_map::TileMap,
+};
+
+/// A tile as written in the text, before the numbers are checked against the mines
+struct ParsedTile {
+    coordinates: Coordinates,
+    mine: bool,
+    state: TileState,
+    number: Option<u8>,
+    line: usize,
+    column: usize,
+}
+
+impl FromStr for TileMap {
+    type Err = TextFormatError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim_end()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let (header_line, header) = lines.next().ok_or(TextFormatError {
+            line: 1,
+            column: 1,
+            kind: TextFormatErrorKind::MissingHeader,
+        })?;
+        let (width, height) = parse_header(header).ok_or_else(|| TextFormatError {
+            line: header_line,
+            column: 1,
+            kind: TextFormatErrorKind::InvalidHeader(header.to_string()),
+        })?;
+        if let Some((column, side)) = oversized_side(header, width, height) {
+            return Err(TextFormatError {
+                line: header_line,
+                column,
+                kind: TextFormatErrorKind::SideTooLarge(side),
+            });
+        }
+
+        let mut tiles = Vec::with_capacity(width as usize * height as usize);
+        let mut rows = 0;
+        let mut last_line = header_line;
+        for (line_number, line) in lines {
+            last_line = line_number;
+            if rows == height as usize {
+                return Err(TextFormatError {
+                    line: line_number,
+                    column: 1,
+                    kind: TextFormatErrorKind::ExtraRow,
+                });
+            }
+
+            let y = height - 1 - rows as u16;
+            let found = line.chars().count();
+            if found != width as usize {
+                return Err(TextFormatError {
+                    line: line_number,
+                    column: found.min(width as usize) + 1,
+                    kind: TextFormatErrorKind::RowLength {
+                        expected: width,
+                        found,
+                    },
+                });
+            }
+
+            for (x, tile) in line.chars().enumerate() {
+                let (mine, state, number) = match tile {
+                    '.' => (false, TileState::Hidden, None),
+                    '*' => (true, TileState::Hidden, None),
+                    'f' => (false, TileState::Flagged, None),
+                    'F' => (true, TileState::Flagged, None),
+                    'q' => (false, TileState::Questioned, None),
+                    'Q' => (true, TileState::Questioned, None),
+                    '@' => (true, TileState::Revealed, None),
+                    'X' => (true, TileState::Exploded, None),
+                    '0'..='8' => (false, TileState::Revealed, Some(tile as u8 - b'0')),
+                    _ => {
+                        return Err(TextFormatError {
+                            line: line_number,
+                            column: x + 1,
+                            kind: TextFormatErrorKind::UnknownTile(tile),
+                        });
+                    }
+                };
+
+                tiles.push(ParsedTile {
+                    coordinates: Coordinates { x: x as u16, y },
+                    mine,
+                    state,
+                    number,
+                    line: line_number,
+                    column: x + 1,
+                });
+            }
+            rows += 1;
+        }
+
+        if rows < height as usize {
+            return Err(TextFormatError {
+                line: last_line + 1,
+                column: 1,
+                kind: TextFormatErrorKind::MissingRows {
+                    expected: height,
+                    found: rows,
+                },
+            });
+        }
+
+        let mines: Vec<Coordinates> = tiles
+            .iter()
+            .filter(|tile| tile.mine)
+            .map(|tile| tile.coordinates)
+            .collect();
+        let mut tile_map = TileMap::with_mines(width, height, &mines);
+
+        for tile in &tiles {
+            if let Some(found) = tile.number {
+                let expected = tile_map.bomb_count_at(tile.coordinates);
+                if found != expected {
+                    return Err(TextFormatError {
+                        line: tile.line,
+                        column: tile.column,
+                        kind: TextFormatErrorKind::WrongNumber { expected, found },
+                    });
+                }
+            }
+            tile_map.set_state(&tile.coordinates, tile.state);
+        }
+
+        Ok(tile_map)
+    }
+}
+
+fn parse_header(header: &str) -> Option<(u16, u16)> {
+    let (width, height) = header.split_once('x')?;
+    let width: u16 = width.trim().parse().ok()?;
+    let height: u16 = height.trim().parse().ok()?;
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+/// Column and length of the first side of a parsed header longer than `MAX_SIDE`, past which the
+/// neighbor offsets overflow
+fn oversized_side(header: &str, width: u16, height: u16) -> Option<(usize, u16)> {
+    let (width_text, height_text) = header.split_once('x')?;
+    let column = |offset: usize, text: &str| offset + text.len() - text.trim_start().len() + 1;
+    if width as u32 > MAX_SIDE {
+        return Some((column(0, width_text), width));
+    }
+    (height as u32 > MAX_SIDE).then(|| (column(width_text.len() + 1, height_text), height))
+}
+
+impl TileMap {
+    /// Writes the board in the text format, `TileMap::from_str` reads it back
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}x{}\n", self.width(), self.height());
+        for row in self.rows().rev() {
+            for tile in row {
+                text.push(match (tile.r#type, tile.state) {
+                    (TileType::Bomb, TileState::Hidden) => '*',
+                    (TileType::Bomb, TileState::Flagged) => 'F',
+                    (TileType::Bomb, TileState::Revealed) => '@',
+                    (TileType::Bomb, TileState::Exploded) => 'X',
+                    (TileType::Bomb, TileState::Questioned) => 'Q',
+                    (_, TileState::Flagged) => 'f',
+                    (_, TileState::Questioned) => 'q',
+                    (TileType::Empty, TileState::Revealed) => '0',
+                    (TileType::Neighbour(n), TileState::Revealed) => (b'0' + n) as char,
+                    _ => '.',
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = "\
+# exploded game with a wrong flag
+4x3
+F2..
+f3*X
+.2@3
+";
+
+    #[test]
+    fn text_round_trip() {
+        let tile_map: TileMap = FIXTURE.parse().unwrap();
+
+        assert_eq!(tile_map.width(), 4);
+        assert_eq!(tile_map.height(), 3);
+        assert_eq!(tile_map.bomb_count, 4);
+        assert_eq!(
+            tile_map.get(&Coordinates { x: 0, y: 2 }).unwrap().state,
+            TileState::Flagged
+        );
+        assert_eq!(tile_map.counts().exploded, 1);
+        assert_eq!(
+            tile_map.to_text(),
+            FIXTURE.lines().skip(1).collect::<Vec<_>>().join("\n") + "\n"
+        );
+        assert_eq!(tile_map.to_text().parse::<TileMap>().unwrap(), tile_map);
+    }
+
+    #[test]
+    fn errors_report_line_and_column() {
+        let error = "2x2\n..\n.?\n".parse::<TileMap>().unwrap_err();
+        assert_eq!((error.line, error.column), (3, 2));
+        assert_eq!(error.kind, TextFormatErrorKind::UnknownTile('?'));
+
+        let error = "# comment\n2x2\n*.\n3.\n".parse::<TileMap>().unwrap_err();
+        assert_eq!((error.line, error.column), (4, 1));
+        assert_eq!(
+            error.kind,
+            TextFormatErrorKind::WrongNumber {
+                expected: 1,
+                found: 3
+            }
+        );
+
+        let error = "3x2\n...\n..\n".parse::<TileMap>().unwrap_err();
+        assert_eq!((error.line, error.column), (3, 3));
+
+        let error = "3x2\n...\n".parse::<TileMap>().unwrap_err();
+        assert_eq!(error.line, 3);
+        assert!(matches!(
+            error.kind,
+            TextFormatErrorKind::MissingRows { .. }
+        ));
+
+        let error = "3by2\n".parse::<TileMap>().unwrap_err();
+        assert!(matches!(error.kind, TextFormatErrorKind::InvalidHeader(_)));
+
+        let error = "# huge\n3x32768\n".parse::<TileMap>().unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+        assert_eq!(error.kind, TextFormatErrorKind::SideTooLarge(32768));
+
+        let error = "65535x1\n".parse::<TileMap>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 1));
+        assert_eq!(error.kind, TextFormatErrorKind::SideTooLarge(65535));
+    }
+}
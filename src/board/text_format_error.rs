@@ -0,0 +1,74 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::board::settings::MAX_SIDE;
+
+/// Error found while parsing a board in the text format, `line` and `column` start at 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextFormatError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: TextFormatErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextFormatErrorKind {
+    /// The text holds no `<width>x<height>` header
+    MissingHeader,
+    InvalidHeader(String),
+    /// A side of the header is longer than `MAX_SIDE`
+    SideTooLarge(u16),
+    UnknownTile(char),
+    RowLength {
+        expected: u16,
+        found: usize,
+    },
+    MissingRows {
+        expected: u16,
+        found: usize,
+    },
+    ExtraRow,
+    /// A revealed number does not match the mines around it
+    WrongNumber {
+        expected: u8,
+        found: u8,
+    },
+}
+
+impl Display for TextFormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            TextFormatErrorKind::MissingHeader => {
+                write!(f, "expected a <width>x<height> header")
+            }
+            TextFormatErrorKind::InvalidHeader(header) => write!(
+                f,
+                "invalid header '{}', expected <width>x<height> with non-zero sizes",
+                header
+            ),
+            TextFormatErrorKind::SideTooLarge(side) => write!(
+                f,
+                "side of {} tiles is too large, width and height can be at most {}",
+                side, MAX_SIDE
+            ),
+            TextFormatErrorKind::UnknownTile(tile) => write!(f, "unknown tile '{}'", tile),
+            TextFormatErrorKind::RowLength { expected, found } => {
+                write!(f, "row has {} tiles, expected {}", found, expected)
+            }
+            TextFormatErrorKind::MissingRows { expected, found } => {
+                write!(f, "found {} rows, expected {}", found, expected)
+            }
+            TextFormatErrorKind::ExtraRow => write!(f, "more rows than the header allows"),
+            TextFormatErrorKind::WrongNumber { expected, found } => write!(
+                f,
+                "revealed number {} does not match the {} mines around it",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl Error for TextFormatError {}
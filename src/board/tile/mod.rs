@@ -1,3 +1,5 @@
+#[allow(clippy::module_inception)]
 pub mod tile;
 pub mod tile_state;
 pub mod tile_type;
+pub mod tile_visual;
@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 use crate::board::tile::{tile_state::TileState, tile_type::TileType};
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Tile {
     pub r#type: TileType,
     pub state: TileState,
@@ -18,10 +20,10 @@ impl Default for Tile {
 impl Tile {
     pub fn reveal(&mut self) {
         match (self.r#type, self.state) {
-            (TileType::Bomb, TileState::Hidden) => {
+            (TileType::Bomb, state) if state.is_covered() => {
                 self.state = TileState::Exploded;
             }
-            (_, TileState::Hidden) => {
+            (_, state) if state.is_covered() => {
                 self.state = TileState::Revealed;
             }
             _ => {}
@@ -29,17 +31,22 @@ impl Tile {
     }
 
     pub fn reveal_without_exploding(&mut self) {
-        if self.state == TileState::Hidden {
+        if self.state.is_covered() {
             self.state = TileState::Revealed;
         }
     }
 
-    pub fn toggle_flag(&mut self) {
+    /// Cycles through the marks: hidden, flagged, then question mark when `question_marks` is
+    /// set, and back to hidden
+    pub fn toggle_flag(&mut self, question_marks: bool) {
         match self.state {
             TileState::Hidden => {
                 self.state = TileState::Flagged;
             }
-            TileState::Flagged => {
+            TileState::Flagged if question_marks => {
+                self.state = TileState::Questioned;
+            }
+            TileState::Flagged | TileState::Questioned => {
                 self.state = TileState::Hidden;
             }
             _ => {}
@@ -1,8 +1,19 @@
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TileState {
     #[default]
     Hidden,
     Revealed,
     Flagged,
     Exploded,
+    /// Marked as uncertain by the player, only used when `GameRules::question_marks` is set
+    Questioned,
+}
+
+impl TileState {
+    /// Whether the tile can still be revealed, question marks do not protect a tile like flags do
+    pub fn is_covered(&self) -> bool {
+        matches!(self, TileState::Hidden | TileState::Questioned)
+    }
 }
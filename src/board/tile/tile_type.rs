@@ -1,7 +1,10 @@
+#[cfg(feature = "bevy")]
 use bevy::ecs::component::Component;
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Component)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bevy", derive(Component))]
 pub enum TileType {
     Empty,
     Bomb,
@@ -33,7 +36,7 @@ impl TileType {
         matches!(self, TileType::Empty)
     }
 
-    pub(crate) fn is_neighbour(&self) -> bool {
+    pub fn is_neighbour(&self) -> bool {
         matches!(self, TileType::Neighbour(_))
     }
 }
@@ -0,0 +1,41 @@
+use crate::board::tile::{tile::Tile, tile_state::TileState, tile_type::TileType};
+
+/// What a tile shows on screen, derived from its type and state. Mistakes are only shown once
+/// the game is lost.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileVisual {
+    Covered,
+    Flag,
+    Question,
+    Empty,
+    Number(u8),
+    Mine,
+    /// A mine the player revealed, which lost the game
+    TriggeredMine,
+    /// A flag on a safe tile, shown as a crossed out mine after a loss
+    WrongFlag,
+}
+
+impl TileVisual {
+    pub fn of(tile: &Tile, game_lost: bool) -> Self {
+        match (tile.state, tile.r#type) {
+            (TileState::Hidden, _) => TileVisual::Covered,
+            (TileState::Flagged, TileType::Bomb) => TileVisual::Flag,
+            (TileState::Flagged, _) if game_lost => TileVisual::WrongFlag,
+            (TileState::Flagged, _) => TileVisual::Flag,
+            (TileState::Questioned, _) => TileVisual::Question,
+            (TileState::Exploded, _) => TileVisual::TriggeredMine,
+            (TileState::Revealed, TileType::Bomb) => TileVisual::Mine,
+            (TileState::Revealed, TileType::Empty) => TileVisual::Empty,
+            (TileState::Revealed, TileType::Neighbour(n)) => TileVisual::Number(n),
+        }
+    }
+
+    /// Whether the tile is drawn on the uncovered background
+    pub fn is_uncovered(&self) -> bool {
+        !matches!(
+            self,
+            TileVisual::Covered | TileVisual::Flag | TileVisual::Question
+        )
+    }
+}
@@ -0,0 +1,9 @@
+use crate::board::{coordinates::Coordinates, tile::tile::Tile};
+
+/// A single tile transition, enough to undo or redo it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileChange {
+    pub coordinates: Coordinates,
+    pub before: Tile,
+    pub after: Tile,
+}
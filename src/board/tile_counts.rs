@@ -0,0 +1,45 @@
+use crate::board::tile::{tile::Tile, tile_state::TileState};
+
+/// Running totals over the tiles of a `TileMap`, kept up to date on every tile transition so
+/// the game state can be checked without scanning the board
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TileCounts {
+    /// Tiles without a bomb that are still hidden
+    pub hidden_safe: usize,
+    /// Flags placed on bombs
+    pub correct_flags: usize,
+    /// Bombs that were revealed
+    pub exploded: usize,
+    /// Tiles without a bomb that were revealed
+    pub revealed: usize,
+}
+
+impl TileCounts {
+    pub fn from_tiles<'a>(tiles: impl IntoIterator<Item = &'a Tile>) -> Self {
+        let mut counts = Self::default();
+        for tile in tiles {
+            counts.add(tile);
+        }
+        counts
+    }
+
+    pub fn add(&mut self, tile: &Tile) {
+        match (tile.r#type.is_bomb(), tile.state) {
+            (false, TileState::Hidden | TileState::Questioned) => self.hidden_safe += 1,
+            (false, TileState::Revealed) => self.revealed += 1,
+            (true, TileState::Flagged) => self.correct_flags += 1,
+            (true, TileState::Exploded) => self.exploded += 1,
+            _ => {}
+        }
+    }
+
+    pub fn remove(&mut self, tile: &Tile) {
+        match (tile.r#type.is_bomb(), tile.state) {
+            (false, TileState::Hidden | TileState::Questioned) => self.hidden_safe -= 1,
+            (false, TileState::Revealed) => self.revealed -= 1,
+            (true, TileState::Flagged) => self.correct_flags -= 1,
+            (true, TileState::Exploded) => self.exploded -= 1,
+            _ => {}
+        }
+    }
+}
@@ -1,48 +1,151 @@
-use bevy::prelude::*;
+use std::{collections::VecDeque, slice::ChunksExact};
 
-use std::ops::{Deref, DerefMut};
-
-use rand::{Rng, rng};
+use rand::{Rng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
 
 use crate::board::{
+    action_outcome::{ActionOutcome, GameStatus},
     coordinates::Coordinates,
+    game_action::GameAction,
+    rules::GameRules,
     tile::{tile::Tile, tile_state::TileState, tile_type::TileType},
+    tile_change::TileChange,
+    tile_counts::TileCounts,
 };
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// Grid of tiles stored row by row in a single contiguous buffer, `y = 0` being the bottom row
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "TileMapData", into = "TileMapData")]
 pub struct TileMap {
     pub bomb_count: u16,
-    pub width: u16,
-    pub height: u16,
-    pub map: Vec<Vec<Tile>>,
+    pub rules: GameRules,
+    width: u16,
+    height: u16,
+    tiles: Vec<Tile>,
+    counts: TileCounts,
+    /// Collects the tile changes while an action is applied
+    journal: Option<Vec<TileChange>>,
+}
+
+/// Serialized form of a `TileMap`, the counts are rebuilt from the tiles when it is loaded
+#[derive(Serialize, Deserialize)]
+struct TileMapData {
+    bomb_count: u16,
+    #[serde(default)]
+    rules: GameRules,
+    width: u16,
+    height: u16,
+    tiles: Vec<Tile>,
+}
+
+impl From<TileMap> for TileMapData {
+    fn from(tile_map: TileMap) -> Self {
+        Self {
+            bomb_count: tile_map.bomb_count,
+            rules: tile_map.rules,
+            width: tile_map.width,
+            height: tile_map.height,
+            tiles: tile_map.tiles,
+        }
+    }
+}
+
+impl TryFrom<TileMapData> for TileMap {
+    type Error = String;
+
+    fn try_from(data: TileMapData) -> Result<Self, Self::Error> {
+        let expected = data.width as usize * data.height as usize;
+        if data.tiles.len() != expected {
+            return Err(format!(
+                "a {}x{} map needs {} tiles, found {}",
+                data.width,
+                data.height,
+                expected,
+                data.tiles.len()
+            ));
+        }
+
+        let mines = data
+            .tiles
+            .iter()
+            .filter(|tile| tile.r#type.is_bomb())
+            .count();
+        if mines != data.bomb_count as usize {
+            return Err(format!(
+                "the map records {} mines but holds {}",
+                data.bomb_count, mines
+            ));
+        }
+
+        Ok(Self {
+            bomb_count: data.bomb_count,
+            rules: data.rules,
+            width: data.width,
+            height: data.height,
+            counts: TileCounts::from_tiles(&data.tiles),
+            tiles: data.tiles,
+            journal: None,
+        })
+    }
 }
 
 impl TileMap {
     pub fn empty(width: u16, height: u16) -> Self {
-        let map: Vec<Vec<Tile>> = (0..height)
-            .map(|_| (0..width).map(|_| Tile::default()).collect::<Vec<Tile>>())
-            .collect();
+        let tiles = vec![Tile::default(); width as usize * height as usize];
 
         Self {
             bomb_count: 0,
+            rules: GameRules::default(),
             width,
             height,
-            map,
+            counts: TileCounts::from_tiles(&tiles),
+            tiles,
+            journal: None,
         }
     }
 
+    /// Builds a map with mines at the given coordinates, mines outside the map are ignored
+    pub fn with_mines(width: u16, height: u16, mines: &[Coordinates]) -> Self {
+        let mut tile_map = Self::empty(width, height);
+        tile_map.set_mines(mines);
+        tile_map
+    }
+
+    /// Adds mines at the given coordinates to a map that has none yet, mines outside the map are
+    /// ignored
+    pub fn set_mines(&mut self, mines: &[Coordinates]) {
+        for coords in mines {
+            if let Some(tile) = self.get_mut(coords)
+                && !tile.r#type.is_bomb()
+            {
+                tile.r#type = TileType::Bomb;
+                self.bomb_count += 1;
+            }
+        }
+
+        self.update_neighbour_counts();
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
     pub fn console_output(&self) -> String {
         let mut buffer = format!(
             "Map ({}, {}) with {} bombs:\n",
             self.width, self.height, self.bomb_count
         );
 
-        let line: String = (0..=(self.width + 1)).into_iter().map(|_| '-').collect();
+        let line: String = (0..=(self.width + 1)).map(|_| '-').collect();
         buffer = format!("{}{}\n", buffer, line);
 
-        for line in self.iter().rev() {
+        for row in self.rows().rev() {
             buffer = format!("{}|", buffer);
-            for tile in line.iter() {
+            for tile in row.iter() {
                 buffer = format!("{}{}", buffer, tile.r#type.console_draw());
             }
             buffer = format!("{}|\n", buffer);
@@ -50,6 +153,122 @@ impl TileMap {
         format!("{}{}", buffer, line)
     }
 
+    /// Index of `coordinates` in the row-major tile buffer, if it is on the map
+    fn index(&self, coordinates: &Coordinates) -> Option<usize> {
+        if !self.coords_in_bounds(*coordinates) {
+            return None;
+        }
+
+        Some(coordinates.y as usize * self.width as usize + coordinates.x as usize)
+    }
+
+    fn coordinates_of(&self, index: usize) -> Coordinates {
+        Coordinates {
+            x: (index % self.width as usize) as u16,
+            y: (index / self.width as usize) as u16,
+        }
+    }
+
+    pub fn get(&self, coordinates: &Coordinates) -> Option<&Tile> {
+        self.index(coordinates).map(|index| &self.tiles[index])
+    }
+
+    /// Puts a tile in `state` without going through the game rules, used when loading boards
+    pub(crate) fn set_state(&mut self, coordinates: &Coordinates, state: TileState) {
+        self.update_tile(coordinates, |tile| tile.state = state);
+    }
+
+    /// Direct access to a tile, callers changing a tile type must `recount` afterwards
+    fn get_mut(&mut self, coordinates: &Coordinates) -> Option<&mut Tile> {
+        self.index(coordinates).map(|index| &mut self.tiles[index])
+    }
+
+    /// Applies a state transition to the tile at `coordinates`, keeping the counts in sync.
+    /// Returns the tile as it was before the transition.
+    fn update_tile(
+        &mut self,
+        coordinates: &Coordinates,
+        transition: impl FnOnce(&mut Tile),
+    ) -> Option<Tile> {
+        let index = self.index(coordinates)?;
+        let before = self.tiles[index];
+        transition(&mut self.tiles[index]);
+        let after = self.tiles[index];
+        self.counts.remove(&before);
+        self.counts.add(&after);
+
+        if let Some(journal) = self.journal.as_mut()
+            && before != after
+        {
+            journal.push(TileChange {
+                coordinates: *coordinates,
+                before,
+                after,
+            });
+        }
+
+        Some(before)
+    }
+
+    /// Runs `f` and returns every tile change it made
+    fn record(&mut self, f: impl FnOnce(&mut Self)) -> Vec<TileChange> {
+        self.journal = Some(Vec::new());
+        f(self);
+        self.journal.take().unwrap_or_default()
+    }
+
+    /// Puts the tiles back in the state they had before `changes`
+    pub fn revert(&mut self, changes: &[TileChange]) {
+        for change in changes.iter().rev() {
+            self.update_tile(&change.coordinates, |tile| *tile = change.before);
+        }
+    }
+
+    /// Applies `changes` again after they were reverted
+    pub fn reapply(&mut self, changes: &[TileChange]) {
+        for change in changes {
+            self.update_tile(&change.coordinates, |tile| *tile = change.after);
+        }
+    }
+
+    fn recount(&mut self) {
+        self.counts = TileCounts::from_tiles(&self.tiles);
+    }
+
+    pub fn counts(&self) -> &TileCounts {
+        &self.counts
+    }
+
+    /// Iterates over the rows from bottom (`y = 0`) to top
+    pub fn rows(&self) -> ChunksExact<'_, Tile> {
+        self.tiles.chunks_exact(self.width.max(1) as usize)
+    }
+
+    pub fn row(&self, y: u16) -> Option<&[Tile]> {
+        self.rows().nth(y as usize)
+    }
+
+    /// Iterates over the coordinates of every tile, row by row
+    pub fn coordinates(&self) -> impl Iterator<Item = Coordinates> + use<> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Coordinates { x, y }))
+    }
+
+    /// Iterates over every tile with its coordinates, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Coordinates, &Tile)> {
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(|(index, tile)| (self.coordinates_of(index), tile))
+    }
+
+    /// Iterates over the neighbors of `coordinates` that are on the map
+    pub fn neighbors(&self, coordinates: Coordinates) -> impl Iterator<Item = Coordinates> + use<> {
+        let (width, height) = (self.width, self.height);
+        self.scan_map_at(coordinates)
+            .filter(move |coord| coord.x < width && coord.y < height)
+    }
+
     pub fn scan_map_at(
         &self,
         coordinates: Coordinates,
@@ -61,13 +280,8 @@ impl TileMap {
     }
 
     pub fn is_bomb_at(&self, coordinates: Coordinates) -> bool {
-        if coordinates.x >= self.width || coordinates.y >= self.height {
-            return false;
-        }
-
-        self.map[coordinates.y as usize][coordinates.x as usize]
-            .r#type
-            .is_bomb()
+        self.get(&coordinates)
+            .is_some_and(|tile| tile.r#type.is_bomb())
     }
 
     pub fn bomb_count_at(&self, coordinates: Coordinates) -> u8 {
@@ -84,167 +298,241 @@ impl TileMap {
             .count() as u8
     }
 
-    pub fn set_bombs(&mut self, bomb_count: u16) {
-        self.bomb_count = bomb_count;
-        let mut remaining_bombs = bomb_count;
-        let mut rng = rng();
+    /// Places `bomb_count` bombs on random tiles outside of `excluded` and computes the neighbour
+    /// counts. When there are fewer free tiles than bombs, every free tile gets a bomb.
+    pub fn set_bombs(&mut self, bomb_count: u16, excluded: &[Coordinates], rng: &mut impl Rng) {
+        let mut candidates: Vec<Coordinates> = self
+            .coordinates()
+            .filter(|coord| !excluded.contains(coord))
+            .collect();
+        let (bombs, _) = candidates.partial_shuffle(rng, bomb_count as usize);
 
-        while remaining_bombs > 0 {
-            let (x, y) = (
-                rng.random_range(0..self.width) as usize,
-                rng.random_range(0..self.height) as usize,
-            );
-            if let TileType::Empty = self[y][x].r#type {
-                self[y][x].r#type = TileType::Bomb;
-                remaining_bombs -= 1;
+        self.bomb_count = bombs.len() as u16;
+        for coord in bombs.iter() {
+            if let Some(tile) = self.get_mut(coord) {
+                tile.r#type = TileType::Bomb;
             }
         }
 
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let coords = Coordinates { x, y };
-                if self.is_bomb_at(coords) {
-                    continue;
-                }
-                let num = self.bomb_count_at(coords);
-                if num == 0 {
-                    continue;
-                }
-                let tile = &mut self[y as usize][x as usize];
-                tile.r#type = TileType::Neighbour(num);
-            }
-        }
+        self.update_neighbour_counts();
     }
 
-    pub(crate) fn at(&self, coord: &Coordinates) -> Option<&Tile> {
-        if coord.x >= self.width || coord.y >= self.height {
-            return None;
+    /// Recomputes the neighbour count of every tile that is not a bomb
+    pub fn update_neighbour_counts(&mut self) {
+        for coords in self.coordinates() {
+            if self.is_bomb_at(coords) {
+                continue;
+            }
+            let num = self.bomb_count_at(coords);
+            if num == 0 {
+                continue;
+            }
+            if let Some(tile) = self.get_mut(&coords) {
+                tile.r#type = TileType::Neighbour(num);
+            }
         }
-
-        Some(&self.map[coord.y as usize][coord.x as usize])
+        self.recount();
     }
 
-    pub fn at_mut(&mut self, coord: &Coordinates) -> Option<&mut Tile> {
-        if coord.x >= self.width || coord.y >= self.height {
-            return None;
-        }
-
-        Some(&mut self.map[coord.y as usize][coord.x as usize])
+    /// Number of flagged tiles around `coordinates`
+    pub fn flags_around(&self, coordinates: Coordinates) -> u8 {
+        self.neighbors(coordinates)
+            .filter(|coord| {
+                self.get(coord)
+                    .is_some_and(|tile| tile.state == TileState::Flagged)
+            })
+            .count() as u8
     }
 
     pub fn coords_in_bounds(&self, coordinates: Coordinates) -> bool {
         coordinates.x < self.width && coordinates.y < self.height
     }
 
-    pub fn reveal_all(&mut self, explode: bool) {
-        for row in self.map.iter_mut() {
-            for tile in row.iter_mut() {
-                if explode {
-                    tile.reveal();
-                } else {
-                    tile.reveal_without_exploding();
+    /// Reveals the mines that are neither flagged nor exploded, leaving the safe tiles and the
+    /// flags as the player left them. Returns the changes.
+    pub fn reveal_mines(&mut self) -> Vec<TileChange> {
+        self.record(|tile_map| {
+            for coordinates in tile_map.coordinates() {
+                if tile_map.is_bomb_at(coordinates) {
+                    tile_map.update_tile(&coordinates, Tile::reveal_without_exploding);
                 }
             }
-        }
+        })
     }
 
-    pub fn reveal_empty_neighbors(
-        &mut self,
-        coordinates: Coordinates,
-        revealed: &mut Vec<Coordinates>,
-    ) {
-        self.scan_map_at(coordinates).for_each(|coord| {
-            if !self.coords_in_bounds(coord) {
-                return;
+    /// Flags placed on safe tiles
+    pub fn wrong_flags(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        self.iter()
+            .filter(|(_, tile)| tile.state == TileState::Flagged && !tile.r#type.is_bomb())
+            .map(|(coordinates, _)| coordinates)
+    }
+
+    /// Reveals every hidden tile, returning the changes
+    pub fn reveal_all(&mut self, explode: bool) -> Vec<TileChange> {
+        self.record(|tile_map| {
+            for coordinates in tile_map.coordinates() {
+                tile_map.update_tile(&coordinates, |tile| {
+                    if explode {
+                        tile.reveal();
+                    } else {
+                        tile.reveal_without_exploding();
+                    }
+                });
             }
+        })
+    }
 
-            if let Some(tile) = self.at_mut(&coord) {
-                if (tile.r#type.is_empty() || tile.r#type.is_neighbour())
-                    && !revealed.contains(&coord)
+    /// Flags every mine that is not flagged yet, shown once the game is won. Returns the changes.
+    pub fn flag_remaining_mines(&mut self) -> Vec<TileChange> {
+        self.record(|tile_map| {
+            for coordinates in tile_map.coordinates() {
+                if tile_map
+                    .get(&coordinates)
+                    .is_some_and(|tile| tile.r#type.is_bomb() && tile.state.is_covered())
                 {
-                    tile.reveal();
-                    revealed.push(coord);
-                    if tile.r#type.is_empty() {
-                        self.reveal_empty_neighbors(coord, revealed);
-                    }
-                } else if tile.r#type.is_neighbour() && !revealed.contains(&coord) {
-                    revealed.push(coord);
+                    tile_map.update_tile(&coordinates, |tile| tile.state = TileState::Flagged);
                 }
             }
-        });
+        })
     }
 
-    /// Reveals the neighbors of a given coordinate, returning a list of all revealed unflagged
-    /// bombs
-    pub fn reveal_neighbors(&mut self, coordinates: Coordinates) -> Vec<Coordinates> {
-        let mut revealed_bombs = Vec::new();
-        self.scan_map_at(coordinates).for_each(|coord| {
-            if !self.coords_in_bounds(coord) {
-                return;
-            }
+    /// Cycles the mark on a tile that is not revealed, returning whether the tile changed
+    pub fn toggle_flag(&mut self, coordinates: Coordinates) -> bool {
+        if !self
+            .get(&coordinates)
+            .is_some_and(|tile| tile.state.is_covered() || tile.state == TileState::Flagged)
+        {
+            return false;
+        }
 
-            if let Some(tile) = self.at_mut(&coord) {
-                tile.reveal();
-                if tile.r#type.is_bomb() && tile.state != TileState::Flagged {
-                    revealed_bombs.push(coord);
-                } else if tile.r#type.is_empty() {
-                    self.reveal_empty_neighbors(coord, &mut revealed_bombs);
-                }
-            }
-        });
+        let question_marks = self.rules.question_marks;
+        self.update_tile(&coordinates, |tile| tile.toggle_flag(question_marks));
+        true
+    }
 
-        revealed_bombs
+    /// Reveals the tile at `coordinates` and flood fills the opening around it when it has no
+    /// neighbouring bombs. Returns the revealed coordinates in BFS order.
+    pub fn reveal_from(&mut self, coordinates: Coordinates) -> Vec<Coordinates> {
+        self.flood_reveal([coordinates])
     }
 
-    pub fn has_won(&self) -> bool {
-        let mut hidden_tiles = 0;
-        let mut flagged_bombs = 0;
+    /// Reveals the neighbors of a given coordinate (chording), flood filling any opening they
+    /// are part of. Returns the revealed coordinates in BFS order, unflagged bombs included.
+    pub fn reveal_neighbors(&mut self, coordinates: Coordinates) -> Vec<Coordinates> {
+        self.flood_reveal(self.neighbors(coordinates))
+    }
 
-        for row in self.map.iter() {
-            for tile in row.iter() {
-                if tile.r#type.is_bomb() && tile.state == TileState::Flagged {
-                    flagged_bombs += 1;
-                }
+    /// Breadth first flood fill from all `starts`. Every hidden start is revealed, and the fill
+    /// spreads through tiles without neighbouring bombs. Tiles are revealed as they are queued,
+    /// so each tile is visited at most once.
+    fn flood_reveal(&mut self, starts: impl IntoIterator<Item = Coordinates>) -> Vec<Coordinates> {
+        let mut revealed = Vec::new();
+        let mut queue = VecDeque::new();
+
+        for start in starts {
+            if self.reveal_hidden(start) {
+                revealed.push(start);
+                queue.push_back(start);
+            }
+        }
 
-                if !tile.r#type.is_bomb() && tile.state == TileState::Hidden {
-                    hidden_tiles += 1;
+        while let Some(coordinates) = queue.pop_front() {
+            if !self
+                .get(&coordinates)
+                .is_some_and(|tile| tile.r#type.is_empty())
+            {
+                continue;
+            }
+
+            for coord in self.neighbors(coordinates) {
+                if self.reveal_hidden(coord) {
+                    revealed.push(coord);
+                    queue.push_back(coord);
                 }
             }
         }
 
-        hidden_tiles == 0 && self.bomb_count == flagged_bombs
+        revealed
     }
 
-    pub fn has_lost(&self) -> bool {
-        for row in self.map.iter() {
-            for tile in row.iter() {
-                if tile.state == TileState::Exploded {
-                    return true;
-                }
-            }
+    /// Reveals the tile if it is hidden or question marked, returning whether it was revealed
+    fn reveal_hidden(&mut self, coordinates: Coordinates) -> bool {
+        if !self
+            .get(&coordinates)
+            .is_some_and(|tile| tile.state.is_covered())
+        {
+            return false;
         }
 
-        false
+        self.update_tile(&coordinates, Tile::reveal);
+        true
     }
 
-    pub(crate) fn is_pristine(&self) -> bool {
-        return self
+    /// Applies a player action to the board. Actions that do not apply to the target tile, such
+    /// as revealing a flagged tile, leave the board untouched. A chord only opens the neighbors of
+    /// a number when exactly that many neighbors are flagged.
+    pub fn apply(&mut self, action: GameAction) -> ActionOutcome {
+        let mut outcome = ActionOutcome::default();
+        let Some(tile) = self.get(&action.coordinates()).copied() else {
+            outcome.status = self.status();
+            return outcome;
+        };
+
+        outcome.changes = self.record(|tile_map| match action {
+            GameAction::Reveal(coordinates) => {
+                if tile.state.is_covered() {
+                    outcome.revealed = tile_map.reveal_from(coordinates);
+                }
+            }
+            GameAction::ToggleFlag(coordinates) => {
+                if tile_map.toggle_flag(coordinates) {
+                    outcome.flags_changed.push(coordinates);
+                }
+            }
+            GameAction::Chord(coordinates) => {
+                if let (TileState::Revealed, TileType::Neighbour(count)) = (tile.state, tile.r#type)
+                    && tile_map.flags_around(coordinates) == count
+                {
+                    outcome.revealed = tile_map.reveal_neighbors(coordinates);
+                }
+            }
+        });
+
+        outcome.mines_hit = outcome
+            .revealed
             .iter()
-            .all(|row| row.iter().all(|tile| tile.state == TileState::Hidden));
+            .copied()
+            .filter(|&coordinates| self.is_bomb_at(coordinates))
+            .collect();
+        outcome.status = self.status();
+        outcome
     }
-}
 
-impl Deref for TileMap {
-    type Target = Vec<Vec<Tile>>;
+    pub fn status(&self) -> GameStatus {
+        if self.has_lost() {
+            GameStatus::Lost
+        } else if self.has_won() {
+            GameStatus::Won
+        } else {
+            GameStatus::Playing
+        }
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.map
+    /// Whether every safe tile is revealed, and every mine flagged under `strict_flags`
+    pub fn has_won(&self) -> bool {
+        let safe_tiles = self.tiles.len() - self.bomb_count as usize;
+        self.counts.revealed == safe_tiles
+            && !self.has_lost()
+            && (!self.rules.strict_flags || self.counts.correct_flags == self.bomb_count as usize)
+    }
+
+    pub fn has_lost(&self) -> bool {
+        self.counts.exploded > 0
     }
-}
 
-impl DerefMut for TileMap {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.map
+    /// Whether no tile has been revealed yet
+    pub fn is_pristine(&self) -> bool {
+        self.counts.revealed == 0 && self.counts.exploded == 0
     }
 }
 
@@ -267,3 +555,251 @@ pub const SQUARE_COORDINATES: [(i8, i8); 8] = [
     // Top right
     (1, 1),
 ];
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::board::tile::tile_visual::TileVisual;
+
+    const ORIGIN: Coordinates = Coordinates { x: 0, y: 0 };
+    const TOP_RIGHT: Coordinates = Coordinates { x: 2, y: 2 };
+
+    /// 3x3 board with a single mine in the bottom left corner
+    fn one_mine() -> TileMap {
+        TileMap::with_mines(3, 3, &[ORIGIN])
+    }
+
+    #[test]
+    fn reveal_from_floods_large_empty_board() {
+        let mut tile_map = TileMap::empty(1000, 1000);
+        let start = Instant::now();
+
+        let revealed = tile_map.reveal_from(Coordinates { x: 500, y: 500 });
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(revealed.len(), 1000 * 1000);
+        assert_eq!(revealed[0], Coordinates { x: 500, y: 500 });
+        assert!(
+            tile_map
+                .iter()
+                .all(|(_, tile)| tile.state == TileState::Revealed)
+        );
+    }
+
+    #[test]
+    fn reveal_from_returns_bfs_order() {
+        let mut tile_map = TileMap::empty(1000, 1000);
+        let revealed = tile_map.reveal_from(ORIGIN);
+
+        let distances: Vec<u16> = revealed.iter().map(|coord| coord.x.max(coord.y)).collect();
+        assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn reveal_from_stops_at_numbers() {
+        let center = Coordinates { x: 2, y: 2 };
+        let mut tile_map = TileMap::with_mines(5, 5, &[center]);
+
+        let revealed = tile_map.reveal_from(Coordinates { x: 0, y: 0 });
+
+        assert_eq!(revealed.len(), 24);
+        assert_eq!(tile_map.get(&center).unwrap().state, TileState::Hidden);
+    }
+
+    #[test]
+    fn reveal_neighbors_floods_large_board() {
+        let mut tile_map = TileMap::with_mines(1000, 1000, &[ORIGIN]);
+        tile_map.toggle_flag(ORIGIN);
+        let number = Coordinates { x: 1, y: 1 };
+        tile_map.reveal_from(number);
+        let start = Instant::now();
+
+        let revealed = tile_map.reveal_neighbors(number);
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(revealed.len(), 1000 * 1000 - 2);
+        assert!(!tile_map.has_lost());
+    }
+
+    #[test]
+    fn reveal_neighbors_reports_unflagged_bombs() {
+        let mut tile_map = one_mine();
+        let number = Coordinates { x: 1, y: 1 };
+        tile_map.reveal_from(number);
+
+        let revealed = tile_map.reveal_neighbors(number);
+
+        assert_eq!(revealed.len(), 8);
+        assert!(revealed.contains(&ORIGIN));
+        assert!(tile_map.has_lost());
+    }
+
+    #[test]
+    fn counts_follow_tile_transitions() {
+        let mut tile_map = one_mine();
+        assert!(tile_map.is_pristine());
+
+        tile_map.reveal_from(TOP_RIGHT);
+        assert_eq!(tile_map.counts().hidden_safe, 0);
+        assert!(!tile_map.is_pristine());
+        assert!(tile_map.has_won());
+
+        tile_map.reveal_from(ORIGIN);
+        assert!(!tile_map.has_won());
+        assert!(tile_map.has_lost());
+        assert_eq!(tile_map.counts(), &TileCounts::from_tiles(&tile_map.tiles));
+    }
+
+    #[test]
+    fn win_needs_safe_tiles_revealed_and_flags_when_strict() {
+        let mut tile_map = one_mine();
+        tile_map.rules.strict_flags = true;
+
+        tile_map.toggle_flag(TOP_RIGHT);
+        tile_map.reveal_from(Coordinates { x: 1, y: 1 });
+        tile_map.reveal_from(Coordinates { x: 1, y: 2 });
+        assert_eq!(tile_map.counts().hidden_safe, 0);
+        assert!(!tile_map.has_won(), "a flagged safe tile is not revealed");
+
+        tile_map.toggle_flag(TOP_RIGHT);
+        tile_map.reveal_from(TOP_RIGHT);
+        assert!(!tile_map.has_won(), "strict mode needs the mine flagged");
+
+        tile_map.rules.strict_flags = false;
+        assert!(tile_map.has_won());
+
+        let changes = tile_map.flag_remaining_mines();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(tile_map.get(&ORIGIN).unwrap().state, TileState::Flagged);
+        tile_map.rules.strict_flags = true;
+        assert!(tile_map.has_won());
+    }
+
+    #[test]
+    fn apply_reports_outcome() {
+        let mut tile_map = one_mine();
+
+        let outcome = tile_map.apply(GameAction::ToggleFlag(ORIGIN));
+        assert_eq!(outcome.flags_changed, vec![ORIGIN]);
+        assert_eq!(outcome.status, GameStatus::Playing);
+
+        let outcome = tile_map.apply(GameAction::Reveal(ORIGIN));
+        assert!(outcome.is_empty());
+
+        let outcome = tile_map.apply(GameAction::Reveal(TOP_RIGHT));
+        assert_eq!(outcome.revealed.len(), 8);
+        assert!(outcome.mines_hit.is_empty());
+        assert_eq!(outcome.status, GameStatus::Won);
+    }
+
+    #[test]
+    fn chord_requires_matching_flag_count() {
+        let mut tile_map = one_mine();
+        let number = Coordinates { x: 1, y: 1 };
+        tile_map.apply(GameAction::Reveal(number));
+
+        let outcome = tile_map.apply(GameAction::Chord(number));
+        assert!(outcome.is_empty());
+
+        tile_map.apply(GameAction::ToggleFlag(ORIGIN));
+        let outcome = tile_map.apply(GameAction::Chord(number));
+        assert_eq!(outcome.revealed.len(), 7);
+        assert!(outcome.mines_hit.is_empty());
+        assert_eq!(outcome.status, GameStatus::Won);
+    }
+
+    #[test]
+    fn question_marks_cycle_and_do_not_count_as_flags() {
+        let mut tile_map = one_mine();
+        tile_map.rules.question_marks = true;
+        let number = Coordinates { x: 1, y: 1 };
+        tile_map.apply(GameAction::Reveal(number));
+
+        let states: Vec<TileState> = (0..3)
+            .map(|_| {
+                tile_map.apply(GameAction::ToggleFlag(TOP_RIGHT));
+                tile_map.get(&TOP_RIGHT).unwrap().state
+            })
+            .collect();
+        assert_eq!(
+            states,
+            [TileState::Flagged, TileState::Questioned, TileState::Hidden]
+        );
+
+        tile_map.apply(GameAction::ToggleFlag(ORIGIN));
+        tile_map.apply(GameAction::ToggleFlag(ORIGIN));
+        assert!(tile_map.apply(GameAction::Chord(number)).is_empty());
+
+        tile_map.apply(GameAction::ToggleFlag(ORIGIN));
+        tile_map.apply(GameAction::ToggleFlag(ORIGIN));
+        tile_map.apply(GameAction::ToggleFlag(TOP_RIGHT));
+        tile_map.apply(GameAction::ToggleFlag(TOP_RIGHT));
+        assert_eq!(tile_map.counts().hidden_safe, 7);
+
+        let outcome = tile_map.apply(GameAction::Chord(number));
+        assert_eq!(outcome.revealed.len(), 7);
+        assert!(outcome.revealed.contains(&TOP_RIGHT));
+        assert_eq!(outcome.status, GameStatus::Won);
+    }
+
+    #[test]
+    fn misflagged_chord_reports_mines_hit() {
+        let mut tile_map = one_mine();
+        let number = Coordinates { x: 1, y: 1 };
+        tile_map.apply(GameAction::Reveal(number));
+        tile_map.apply(GameAction::ToggleFlag(Coordinates { x: 2, y: 0 }));
+
+        let outcome = tile_map.apply(GameAction::Chord(number));
+
+        assert_eq!(outcome.mines_hit, vec![ORIGIN]);
+        assert_eq!(outcome.status, GameStatus::Lost);
+    }
+
+    #[test]
+    fn defeat_reveals_mines_and_keeps_flags() {
+        let mut tile_map: TileMap = "3x2\nF*f\n..*\n".parse().unwrap();
+        tile_map.apply(GameAction::Reveal(Coordinates { x: 1, y: 1 }));
+        let changes = tile_map.reveal_mines();
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(tile_map.to_text(), "3x2\nFXf\n..@\n");
+        assert_eq!(
+            tile_map.wrong_flags().collect::<Vec<_>>(),
+            vec![Coordinates { x: 2, y: 1 }]
+        );
+
+        let visuals: Vec<TileVisual> = tile_map
+            .row(1)
+            .unwrap()
+            .iter()
+            .map(|tile| TileVisual::of(tile, tile_map.has_lost()))
+            .collect();
+        assert_eq!(
+            visuals,
+            [
+                TileVisual::Flag,
+                TileVisual::TriggeredMine,
+                TileVisual::WrongFlag
+            ]
+        );
+    }
+
+    #[test]
+    fn revert_restores_tiles_and_counts() {
+        let mut tile_map = one_mine();
+        let pristine = tile_map.clone();
+
+        let outcome = tile_map.apply(GameAction::Reveal(ORIGIN));
+        assert!(tile_map.has_lost());
+        let defeat = tile_map.reveal_all(false);
+
+        tile_map.revert(&defeat);
+        tile_map.revert(&outcome.changes);
+        assert_eq!(tile_map, pristine);
+
+        tile_map.reapply(&outcome.changes);
+        assert!(tile_map.has_lost());
+    }
+}
@@ -0,0 +1,10 @@
+#[cfg(feature = "bevy")]
+pub mod assist;
+pub mod board;
+pub mod replay;
+pub mod save;
+pub mod solver;
+#[cfg(feature = "bevy")]
+pub mod ui;
+#[cfg(feature = "bevy")]
+pub mod utils;
@@ -1,15 +1,19 @@
 #[cfg(feature = "debug")]
 use bevy::dev_tools::fps_overlay::FpsOverlayPlugin;
-use bevy::{prelude::*, window::PresentMode};
+use std::{env, path::Path};
 
-use crate::{
-    board::{board_plugin::BoardPlugin, settings::BoardSettings},
-    ui::{settings::UiSettings, ui_plugin::UiPlugin},
-};
+use bevy::{log, prelude::*, window::PresentMode};
 
-pub mod board;
-pub mod ui;
-pub mod utils;
+use bevy_minesweeper::{
+    assist::{
+        autoplay_plugin::AutoplayPlugin, heatmap_plugin::HeatmapPlugin, hint_plugin::HintPlugin,
+    },
+    board::{board_plugin::BoardPlugin, preset_board::PresetBoard, rules::GameRules},
+    replay::replay_plugin::ReplayPlugin,
+    save::save_plugin::SavePlugin,
+    ui::{menu_error::MenuError, settings::UiSettings, ui_plugin::UiPlugin},
+    utils::app_state::AppState,
+};
 
 fn main() {
     let mut app = App::new();
@@ -27,10 +31,16 @@ fn main() {
             }),
     )
     .insert_resource(UiSettings::default())
+    .insert_resource(GameRules::default())
     .insert_resource(ClearColor(Color::srgb(0.3, 0.3, 0.3)))
     .add_plugins(UiPlugin)
     .add_plugins(BoardPlugin)
-    .add_systems(Startup, spawn);
+    .add_plugins(ReplayPlugin)
+    .add_plugins(SavePlugin)
+    .add_plugins(HeatmapPlugin)
+    .add_plugins(HintPlugin)
+    .add_plugins(AutoplayPlugin)
+    .add_systems(Startup, (spawn, load_board_file));
 
     #[cfg(feature = "debug")]
     let app = app.add_plugins(FpsOverlayPlugin::default());
@@ -48,3 +58,25 @@ pub fn spawn(mut commands: Commands) {
         },
     ));
 }
+
+/// Starts straight on the board file given as the first argument
+pub fn load_board_file(mut commands: Commands, mut next_state: ResMut<NextState<AppState>>) {
+    let Some(path) = env::args().nth(1) else {
+        return;
+    };
+
+    let preset =
+        PresetBoard::load(Path::new(&path)).and_then(|preset| Ok((preset.settings()?, preset)));
+    match preset {
+        Ok((settings, preset)) => {
+            log::info!("Loaded board from {}", path);
+            commands.insert_resource(settings);
+            commands.insert_resource(preset);
+            next_state.set(AppState::InGame);
+        }
+        Err(error) => {
+            log::error!("Failed to load board from {}: {}", path, error);
+            commands.insert_resource(MenuError(error.to_string()));
+        }
+    }
+}
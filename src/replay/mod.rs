@@ -0,0 +1,7 @@
+#[allow(clippy::module_inception)]
+pub mod replay;
+pub mod replay_error;
+#[cfg(feature = "bevy")]
+pub mod replay_player;
+#[cfg(feature = "bevy")]
+pub mod replay_plugin;
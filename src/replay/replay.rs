@@ -0,0 +1,164 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{game_action::GameAction, settings::BoardSettings, tile_map::TileMap},
+    replay::replay_error::ReplayError,
+};
+
+/// Version of the replay file format, bumped on every incompatible change
+pub const REPLAY_VERSION: u32 = 1;
+
+/// Everything needed to play a game back: the settings (seed included) rebuild the same board,
+/// and the events repeat the player's moves at the time they were made
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    /// Seconds since the Unix epoch when the game started
+    pub recorded_at: u64,
+    pub settings: BoardSettings,
+    /// Board the game started from when it was loaded from a file rather than generated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<TileMap>,
+    pub events: Vec<ReplayEvent>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    /// Seconds since the start of the game
+    pub time: f32,
+    pub step: ReplayStep,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplayStep {
+    Action(GameAction),
+    Undo,
+    Redo,
+}
+
+impl Replay {
+    pub fn new(settings: BoardSettings, recorded_at: u64) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            recorded_at,
+            settings,
+            preset: None,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, time: f32, step: ReplayStep) {
+        self.events.push(ReplayEvent { time, step });
+    }
+
+    /// Length of the replay in seconds
+    pub fn duration(&self) -> f32 {
+        self.events.last().map_or(0.0, |event| event.time)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Saves the replay in a new file of the directory. Files are named after the second the game
+    /// started and a counter, so games started in the same second do not overwrite each other.
+    pub fn save_new(&self, dir: &Path) -> Result<PathBuf, ReplayError> {
+        let path = (0..)
+            .map(|counter| dir.join(format!("{}-{}.json", self.recorded_at, counter)))
+            .find(|path| !path.exists())
+            .expect("a free replay file name");
+        self.save(&path)?;
+        Ok(path)
+    }
+
+    /// Orders replay files by the start of their game, then by their counter. Files named after
+    /// the second alone, as before the counter, come first within their second.
+    pub fn file_order(path: &Path) -> Option<(u64, u32)> {
+        if path.extension()? != "json" {
+            return None;
+        }
+
+        let stem = path.file_stem()?.to_str()?;
+        match stem.split_once('-') {
+            Some((recorded_at, counter)) => {
+                Some((recorded_at.parse().ok()?, counter.parse().ok()?))
+            }
+            None => Some((stem.parse().ok()?, 0)),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a replay, checking the format version before anything else
+    pub fn from_json(json: &str) -> Result<Self, ReplayError> {
+        #[derive(Deserialize)]
+        struct Header {
+            version: u32,
+        }
+
+        let header: Header = serde_json::from_str(json)?;
+        if header.version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(header.version));
+        }
+
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::coordinates::Coordinates;
+
+    #[test]
+    fn json_round_trip() {
+        let settings = BoardSettings::new(9, 9, 10).unwrap().with_seed(42);
+        let mut replay = Replay::new(settings, 1_700_000_000);
+        replay.record(
+            0.5,
+            ReplayStep::Action(GameAction::Reveal(Coordinates { x: 4, y: 4 })),
+        );
+        replay.record(1.25, ReplayStep::Undo);
+
+        let json = serde_json::to_string(&replay).unwrap();
+        assert_eq!(Replay::from_json(&json).unwrap(), replay);
+        assert_eq!(replay.duration(), 1.25);
+    }
+
+    #[test]
+    fn orders_files_by_second_then_counter() {
+        let order = |name: &str| Replay::file_order(Path::new(name));
+
+        assert_eq!(order("1700000000.json"), Some((1_700_000_000, 0)));
+        assert_eq!(order("1700000000-2.json"), Some((1_700_000_000, 2)));
+        assert!(order("1700000000-2.json") > order("1700000000-1.json"));
+        assert!(order("1700000001-0.json") > order("1700000000-9.json"));
+        assert_eq!(order("1700000000-2.txt"), None);
+        assert_eq!(order("notes.json"), None);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let settings = BoardSettings::new(9, 9, 10).unwrap();
+        let mut replay = Replay::new(settings, 0);
+        replay.version = REPLAY_VERSION + 1;
+
+        let json = serde_json::to_string(&replay).unwrap();
+        assert!(matches!(
+            Replay::from_json(&json),
+            Err(ReplayError::UnsupportedVersion(version)) if version == REPLAY_VERSION + 1
+        ));
+    }
+}
@@ -0,0 +1,55 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+use crate::replay::replay::REPLAY_VERSION;
+
+#[derive(Debug)]
+pub enum ReplayError {
+    /// No game was recorded yet
+    NoReplay,
+    Io(io::Error),
+    /// The file is not a valid replay
+    Format(serde_json::Error),
+    /// The replay was written by a version of the game this one can not play back
+    UnsupportedVersion(u32),
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::NoReplay => write!(f, "No replay found, finish a game first"),
+            ReplayError::Io(error) => write!(f, "Could not access replay: {}", error),
+            ReplayError::Format(error) => write!(f, "Invalid replay file: {}", error),
+            ReplayError::UnsupportedVersion(version) => write!(
+                f,
+                "Replay version {} is not supported, expected version {}",
+                version, REPLAY_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReplayError::Io(error) => Some(error),
+            ReplayError::Format(error) => Some(error),
+            ReplayError::NoReplay | ReplayError::UnsupportedVersion(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ReplayError {
+    fn from(error: serde_json::Error) -> Self {
+        ReplayError::Format(error)
+    }
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+
+use crate::replay::replay::{Replay, ReplayEvent};
+
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 8.0;
+
+/// Playback position within the replay being watched
+#[derive(Resource, Debug)]
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    /// Index of the next event to play
+    pub cursor: usize,
+    /// Seconds of the recording played so far
+    pub clock: f32,
+    pub speed: f32,
+    pub paused: bool,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            cursor: 0,
+            clock: 0.0,
+            speed: 1.0,
+            paused: false,
+        }
+    }
+
+    /// Advances the clock and returns the events that are due
+    pub fn advance(&mut self, delta: f32) -> Vec<ReplayEvent> {
+        if !self.paused {
+            self.clock += delta * self.speed;
+        }
+
+        let due = self.replay.events[self.cursor..]
+            .iter()
+            .take_while(|event| event.time <= self.clock)
+            .copied()
+            .collect::<Vec<_>>();
+        self.cursor += due.len();
+        due
+    }
+
+    /// Plays the next event right away, moving the clock to it
+    pub fn step(&mut self) -> Option<ReplayEvent> {
+        let event = *self.replay.events.get(self.cursor)?;
+        self.cursor += 1;
+        self.clock = self.clock.max(event.time);
+        Some(event)
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed * 2.0).min(MAX_SPEED);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = (self.speed / 2.0).max(MIN_SPEED);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.replay.events.len()
+    }
+}
@@ -0,0 +1,230 @@
+use std::fs;
+
+use bevy::{log, prelude::*};
+
+use crate::{
+    board::{
+        action_outcome::GameStatus,
+        board::Board,
+        board_changed::BoardChanged,
+        board_plugin::BoardPlugin,
+        pending_board::{PendingBoard, PendingBoardText},
+        settings::BoardSettings,
+    },
+    replay::{
+        replay::{Replay, ReplayEvent, ReplayStep},
+        replay_error::ReplayError,
+        replay_player::ReplayPlayer,
+    },
+    ui::menu_error::MenuError,
+    utils::{app_state::AppState, data_dir::replay_dir},
+};
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnTransition {
+                exited: AppState::MainMenu,
+                entered: AppState::Replay,
+            },
+            (
+                BoardPlugin::clear_board,
+                BoardPlugin::create_board,
+                Self::setup_hud,
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                Self::playback_controls,
+                Self::finish_pending_board.run_if(resource_exists::<PendingBoard>),
+                Self::play_events.run_if(not(resource_exists::<PendingBoard>)),
+                Self::update_hud,
+                BoardPlugin::update_board,
+                BoardPlugin::leave_on_error.run_if(resource_exists_and_changed::<MenuError>),
+            )
+                .chain()
+                .run_if(in_state(AppState::Replay)),
+        )
+        .add_systems(
+            OnExit(AppState::Replay),
+            (Self::cleanup_replay, BoardPlugin::cancel_pending_board),
+        )
+        .add_systems(OnEnter(AppState::Victory), Self::save_replay)
+        .add_systems(OnEnter(AppState::Defeat), Self::save_replay);
+    }
+}
+
+#[derive(Component)]
+pub struct ReplayHud;
+
+impl ReplayPlugin {
+    /// Loads the most recently recorded game
+    pub fn load_latest() -> Result<Replay, ReplayError> {
+        let latest = fs::read_dir(replay_dir())
+            .map_err(|_| ReplayError::NoReplay)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter_map(|path| Replay::file_order(&path).map(|order| (order, path)))
+            .max_by_key(|(order, _)| *order)
+            .map(|(_, path)| path)
+            .ok_or(ReplayError::NoReplay)?;
+
+        log::info!("Loading replay {}", latest.display());
+        Replay::load(&latest)
+    }
+
+    /// Saves the replay of the game that just ended, once per board. Ending the game again after
+    /// undoing a loss updates the same file.
+    pub fn save_replay(mut board: Single<&mut Board>) {
+        let saved = match &board.replay_path {
+            Some(path) => board.recording.save(path).map(|()| path.clone()),
+            None => board.recording.save_new(&replay_dir()),
+        };
+        match saved {
+            Ok(path) => {
+                log::info!("Saved replay to {}", path.display());
+                board.replay_path = Some(path);
+            }
+            Err(error) => log::error!("Failed to save replay: {}", error),
+        }
+    }
+
+    pub fn playback_controls(
+        keys: Res<ButtonInput<KeyCode>>,
+        mut player: ResMut<ReplayPlayer>,
+        mut next_state: ResMut<NextState<AppState>>,
+    ) {
+        if keys.just_pressed(KeyCode::Space) {
+            player.paused = !player.paused;
+        }
+        if keys.just_pressed(KeyCode::ArrowUp) {
+            player.faster();
+        }
+        if keys.just_pressed(KeyCode::ArrowDown) {
+            player.slower();
+        }
+        if keys.just_pressed(KeyCode::Escape) {
+            next_state.set(AppState::MainMenu);
+        }
+    }
+
+    /// Applies the recorded events that are due, or the next one when stepping frame by frame.
+    /// The first reveal of a board without guesses waits in the player, with the events after
+    /// it, until the board is generated off the frame.
+    pub fn play_events(
+        mut commands: Commands,
+        time: Res<Time>,
+        keys: Res<ButtonInput<KeyCode>>,
+        mut player: ResMut<ReplayPlayer>,
+        mut board: Single<&mut Board>,
+        board_settings: Res<BoardSettings>,
+        mut board_changed_event: EventWriter<BoardChanged>,
+    ) {
+        let mut events = player.advance(time.delta_secs());
+        if player.paused
+            && keys.just_pressed(KeyCode::ArrowRight)
+            && let Some(event) = player.step()
+        {
+            events.push(event);
+        }
+
+        for (index, ReplayEvent { step, .. }) in events.iter().copied().enumerate() {
+            let coordinates = match step {
+                ReplayStep::Action(action)
+                    if board_settings.no_guess && board.places_mines(action) =>
+                {
+                    player.cursor -= events.len() - index;
+                    BoardPlugin::start_pending_board(
+                        &mut commands,
+                        &board,
+                        action,
+                        &board_settings,
+                    );
+                    break;
+                }
+                ReplayStep::Action(action) => {
+                    let outcome = board.apply(action, &board_settings);
+                    let mut coordinates = outcome.changed().collect::<Vec<_>>();
+                    match outcome.status {
+                        GameStatus::Lost => coordinates.extend(board.reveal_on_defeat()),
+                        GameStatus::Won => coordinates.extend(board.flag_on_victory()),
+                        GameStatus::Playing => {}
+                    }
+                    coordinates
+                }
+                ReplayStep::Undo => board.undo().unwrap_or_default(),
+                ReplayStep::Redo => board.redo().unwrap_or_default(),
+            };
+
+            if !coordinates.is_empty() {
+                board_changed_event.write(BoardChanged { coordinates });
+            }
+        }
+    }
+
+    /// Places the mines of a replayed board without guesses, its first reveal then plays from
+    /// the player
+    pub fn finish_pending_board(
+        mut commands: Commands,
+        mut pending: ResMut<PendingBoard>,
+        mut board: Single<&mut Board>,
+        board_settings: Res<BoardSettings>,
+        texts: Query<Entity, With<PendingBoardText>>,
+    ) {
+        BoardPlugin::place_pending_mines(
+            &mut commands,
+            &mut pending,
+            &mut board,
+            &board_settings,
+            &texts,
+        );
+    }
+
+    pub fn setup_hud(mut commands: Commands) {
+        commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.),
+                left: Val::Px(10.),
+                ..default()
+            },
+            ReplayHud,
+            Text::default(),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+        ));
+    }
+
+    pub fn update_hud(player: Res<ReplayPlayer>, mut hud: Single<&mut Text, With<ReplayHud>>) {
+        let status = if player.is_finished() {
+            "finished"
+        } else if player.paused {
+            "paused"
+        } else {
+            "playing"
+        };
+
+        hud.0 = format!(
+            "Replay {:.1}s / {:.1}s, {}x, {}\n\
+             Space: pause, Right: step, Up/Down: speed, Esc: menu",
+            player.clock.min(player.replay.duration()),
+            player.replay.duration(),
+            player.speed,
+            status
+        );
+    }
+
+    pub fn cleanup_replay(mut commands: Commands, hud: Query<Entity, With<ReplayHud>>) {
+        for entity in &hud {
+            commands.entity(entity).despawn();
+        }
+        commands.remove_resource::<ReplayPlayer>();
+    }
+}
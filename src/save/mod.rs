@@ -0,0 +1,4 @@
+pub mod save_error;
+pub mod save_game;
+#[cfg(feature = "bevy")]
+pub mod save_plugin;
@@ -0,0 +1,57 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+use crate::save::save_game::SAVE_VERSION;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    /// The file is not a valid save
+    Format(serde_json::Error),
+    /// The save was written by a version of the game this one can not load
+    UnsupportedVersion(u32),
+    /// The saved map does not have the size given by the saved settings
+    SizeMismatch,
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(error) => write!(f, "Could not access save: {}", error),
+            SaveError::Format(error) => write!(f, "Invalid save file: {}", error),
+            SaveError::UnsupportedVersion(version) => write!(
+                f,
+                "Save version {} is not supported, expected version {}",
+                version, SAVE_VERSION
+            ),
+            SaveError::SizeMismatch => {
+                write!(f, "Invalid save file: the map does not match its settings")
+            }
+        }
+    }
+}
+
+impl Error for SaveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SaveError::Io(error) => Some(error),
+            SaveError::Format(error) => Some(error),
+            SaveError::UnsupportedVersion(_) | SaveError::SizeMismatch => None,
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(error: serde_json::Error) -> Self {
+        SaveError::Format(error)
+    }
+}
@@ -0,0 +1,129 @@
+use std::{fs, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{action_counters::ActionCounters, settings::BoardSettings, tile_map::TileMap},
+    replay::replay::Replay,
+    save::save_error::SaveError,
+};
+
+/// Version of the save file format, bumped on every incompatible change
+pub const SAVE_VERSION: u32 = 1;
+
+/// A game in progress, written when the player leaves so it can be continued later
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub settings: BoardSettings,
+    pub tile_map: TileMap,
+    pub mines_placed: bool,
+    pub elapsed: Duration,
+    pub actions: ActionCounters,
+    pub assisted: bool,
+    /// Recording of the game so far, the replay keeps going once the game is continued
+    pub recording: Replay,
+}
+
+impl SaveGame {
+    pub fn save(&self, path: &Path) -> Result<(), SaveError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, SaveError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a save, checking the format version before anything else
+    pub fn from_json(json: &str) -> Result<Self, SaveError> {
+        #[derive(Deserialize)]
+        struct Header {
+            version: u32,
+        }
+
+        let header: Header = serde_json::from_str(json)?;
+        if header.version != SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion(header.version));
+        }
+
+        let save: Self = serde_json::from_str(json)?;
+        if save.tile_map.width() != save.settings.board_width
+            || save.tile_map.height() != save.settings.board_height
+        {
+            return Err(SaveError::SizeMismatch);
+        }
+
+        Ok(save)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::board::{coordinates::Coordinates, game_action::GameAction};
+
+    fn save_game() -> SaveGame {
+        let settings = BoardSettings::new(9, 9, 10).unwrap().with_seed(7);
+        let mut tile_map = TileMap::empty(9, 9);
+        tile_map.set_bombs(10, &[], &mut ChaCha8Rng::seed_from_u64(7));
+        tile_map.apply(GameAction::ToggleFlag(Coordinates { x: 0, y: 0 }));
+
+        SaveGame {
+            version: SAVE_VERSION,
+            recording: Replay::new(settings.clone(), 0),
+            settings,
+            tile_map,
+            mines_placed: true,
+            elapsed: Duration::from_millis(12_500),
+            actions: ActionCounters {
+                flags: 1,
+                ..Default::default()
+            },
+            assisted: false,
+        }
+    }
+
+    #[test]
+    fn json_round_trip_rebuilds_counts() {
+        let save = save_game();
+
+        let json = serde_json::to_string(&save).unwrap();
+        let loaded = SaveGame::from_json(&json).unwrap();
+        assert_eq!(loaded, save);
+        assert_eq!(loaded.tile_map.counts(), save.tile_map.counts());
+    }
+
+    #[test]
+    fn rejects_mine_count_not_matching_tiles() {
+        for bomb_count in [9, u16::MAX] {
+            let mut save = save_game();
+            save.tile_map.bomb_count = bomb_count;
+
+            let json = serde_json::to_string(&save).unwrap();
+            assert!(matches!(
+                SaveGame::from_json(&json),
+                Err(SaveError::Format(error)) if error.to_string().contains("records")
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_map_not_matching_settings() {
+        let mut save = save_game();
+        save.settings.board_width = 10;
+
+        let json = serde_json::to_string(&save).unwrap();
+        assert!(matches!(
+            SaveGame::from_json(&json),
+            Err(SaveError::SizeMismatch)
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+use std::{fs, io};
+
+use bevy::{log, prelude::*, window::WindowCloseRequested};
+
+use crate::{
+    board::{
+        board::Board, board_changed::BoardChanged, board_plugin::BoardPlugin,
+        settings::BoardSettings,
+    },
+    save::{save_error::SaveError, save_game::SaveGame},
+    utils::{app_state::AppState, data_dir::save_path},
+};
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnTransition {
+                exited: AppState::MainMenu,
+                entered: AppState::InGame,
+            },
+            Self::restore_game
+                .after(BoardPlugin::create_board)
+                .run_if(resource_exists::<ResumedGame>),
+        )
+        .add_systems(
+            Update,
+            (
+                Self::autosave_on_close.run_if(on_event::<WindowCloseRequested>),
+                Self::save_and_leave,
+            )
+                .run_if(in_state(AppState::InGame)),
+        )
+        .add_systems(OnEnter(AppState::Victory), Self::delete_save)
+        .add_systems(OnEnter(AppState::Defeat), Self::delete_save);
+    }
+}
+
+/// Saved game to continue once the board is created
+#[derive(Resource)]
+pub struct ResumedGame(pub SaveGame);
+
+impl SavePlugin {
+    pub fn has_save() -> bool {
+        save_path().exists()
+    }
+
+    pub fn load() -> Result<SaveGame, SaveError> {
+        SaveGame::load(&save_path())
+    }
+
+    fn write_save(board: &Board, board_settings: &BoardSettings) {
+        let path = save_path();
+        match board.to_save(board_settings).save(&path) {
+            Ok(()) => log::info!("Saved game to {}", path.display()),
+            Err(error) => log::error!("Failed to save game: {}", error),
+        }
+    }
+
+    pub fn autosave_on_close(board: Single<&Board>, board_settings: Res<BoardSettings>) {
+        Self::write_save(&board, &board_settings);
+    }
+
+    /// Saves the game and goes back to the main menu when Escape is pressed
+    pub fn save_and_leave(
+        keys: Res<ButtonInput<KeyCode>>,
+        board: Single<&Board>,
+        board_settings: Res<BoardSettings>,
+        mut next_state: ResMut<NextState<AppState>>,
+    ) {
+        if keys.just_pressed(KeyCode::Escape) {
+            Self::write_save(&board, &board_settings);
+            next_state.set(AppState::MainMenu);
+        }
+    }
+
+    pub fn restore_game(
+        mut commands: Commands,
+        resumed: Res<ResumedGame>,
+        mut board: Single<&mut Board>,
+        mut board_changed_event: EventWriter<BoardChanged>,
+    ) {
+        let coordinates = board.restore(resumed.0.clone());
+        log::info!(
+            "Continuing saved game after {:.1}s and {} moves",
+            board.elapsed.as_secs_f32(),
+            board.actions.total()
+        );
+        board_changed_event.write(BoardChanged { coordinates });
+        commands.remove_resource::<ResumedGame>();
+    }
+
+    /// A finished game can not be continued
+    pub fn delete_save() {
+        match fs::remove_file(save_path()) {
+            Ok(()) => log::info!("Removed saved game"),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => log::error!("Failed to remove saved game: {}", error),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Stress tests playing the bot over thousands of games through the board the autoplay drives
+
+use bevy::tasks::block_on;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    board::{
+        action_outcome::GameStatus, board::Board, coordinates::Coordinates,
+        game_action::GameAction, rules::GameRules, settings::BoardSettings,
+        tile_counts::TileCounts,
+    },
+    solver::hint::Hint,
+};
+
+/// Plays a game to the end with `Board::next_move`, the moves the autoplay makes, checking the
+/// rules after every move. Returns the final status and whether the bot had to guess.
+fn play_checked(
+    board: &mut Board,
+    board_settings: &BoardSettings,
+    first_click: Coordinates,
+) -> (GameStatus, bool) {
+    let mut status = board
+        .apply(GameAction::Reveal(first_click), board_settings)
+        .status;
+    let mut guessed = false;
+    while status == GameStatus::Playing {
+        let hint = board
+            .next_move(board_settings)
+            .expect("the bot always has a move");
+        let tile_map = &board.tile_map;
+        match hint {
+            Hint::Safe(cell) => assert!(!tile_map.is_bomb_at(cell), "{} is a mine", cell),
+            Hint::Mine(cell) => assert!(tile_map.is_bomb_at(cell), "{} is safe", cell),
+            Hint::Guess { probability, .. } => {
+                assert!((0.0..1.0).contains(&probability));
+                guessed = true;
+            }
+        }
+
+        let outcome = board.apply(hint.action(), board_settings);
+        assert!(!outcome.is_empty(), "{:?} did not change the board", hint);
+        assert_eq!(
+            *board.tile_map.counts(),
+            TileCounts::from_tiles(board.tile_map.iter().map(|(_, tile)| tile))
+        );
+        if outcome.status == GameStatus::Lost {
+            assert!(
+                matches!(hint, Hint::Guess { .. }),
+                "{:?} lost the game",
+                hint
+            );
+            assert_eq!(outcome.mines_hit, vec![hint.coordinates()]);
+        }
+        status = outcome.status;
+    }
+
+    if status == GameStatus::Won {
+        assert_eq!(board.tile_map.counts().hidden_safe, 0);
+    }
+    (status, guessed)
+}
+
+#[test]
+fn bot_games_keep_the_rules() {
+    let mut rng = ChaCha8Rng::seed_from_u64(25);
+    for (width, height, mines, games) in [(9, 9, 10, 2000), (16, 16, 40, 200), (30, 16, 99, 30)] {
+        let mut won = 0;
+        for game in 0..games {
+            let board_settings = BoardSettings::new(width as u32, height as u32, mines)
+                .unwrap()
+                .with_seed(rng.random())
+                .with_rules(GameRules {
+                    question_marks: game % 2 == 0,
+                    strict_flags: game % 3 == 0,
+                });
+            let first_click = Coordinates {
+                x: rng.random_range(0..width),
+                y: rng.random_range(0..height),
+            };
+            let mut board = Board::new(&board_settings);
+
+            if play_checked(&mut board, &board_settings, first_click).0 == GameStatus::Won {
+                won += 1;
+            }
+        }
+        assert!(
+            won * 4 >= games,
+            "won {} of {} games on {}x{} with {} mines",
+            won,
+            games,
+            width,
+            height,
+            mines
+        );
+    }
+}
+
+#[test]
+fn bot_never_guesses_on_no_guess_boards() {
+    let first_click = Coordinates { x: 8, y: 8 };
+    for seed in 0..100 {
+        let board_settings = BoardSettings::new(16, 16, 40)
+            .unwrap()
+            .with_seed(seed)
+            .with_no_guess(true);
+        let mut board = Board::new(&board_settings);
+        let generated = block_on(board.generate_mines_task(first_click, &board_settings));
+        let mines = generated.mines.expect("a board without guesses");
+        board.place_generated_mines(first_click, &mines, generated.rng, &board_settings);
+
+        let (status, guessed) = play_checked(&mut board, &board_settings, first_click);
+        assert_eq!(status, GameStatus::Won);
+        assert!(!guessed);
+    }
+}
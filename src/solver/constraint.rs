@@ -0,0 +1,28 @@
+use std::collections::BTreeSet;
+
+use crate::board::coordinates::Coordinates;
+
+/// Exactly `mines` of the `cells` hold a mine, as told by the revealed number at `source`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Constraint {
+    pub source: Coordinates,
+    pub cells: BTreeSet<Coordinates>,
+    pub mines: usize,
+}
+
+impl Constraint {
+    pub fn is_subset_of(&self, other: &Constraint) -> bool {
+        self.cells.is_subset(&other.cells)
+    }
+
+    pub fn overlaps(&self, other: &Constraint) -> bool {
+        !self.cells.is_disjoint(&other.cells)
+    }
+
+    /// Drops a cell whose content is known, keeping the mine count in line
+    pub fn resolve(&mut self, cell: Coordinates, mine: bool) {
+        if self.cells.remove(&cell) && mine {
+            self.mines = self.mines.saturating_sub(1);
+        }
+    }
+}
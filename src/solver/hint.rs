@@ -0,0 +1,116 @@
+use crate::{
+    board::{
+        coordinates::Coordinates, game_action::GameAction, tile::tile_state::TileState,
+        tile_map::TileMap,
+    },
+    solver::{
+        probability::probabilities_for_state, solver::solve_state, visible_state::VisibleState,
+    },
+};
+
+/// Next move suggested from what the player can see of the board
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Hint {
+    /// A cell proven to be safe
+    Safe(Coordinates),
+    /// A cell proven to be a mine that is not flagged yet
+    Mine(Coordinates),
+    /// No cell is certain, this one is the least likely to hold a mine
+    Guess {
+        coordinates: Coordinates,
+        probability: f64,
+    },
+}
+
+impl Hint {
+    pub fn coordinates(&self) -> Coordinates {
+        match self {
+            Hint::Safe(coordinates) | Hint::Mine(coordinates) => *coordinates,
+            Hint::Guess { coordinates, .. } => *coordinates,
+        }
+    }
+
+    /// Action playing the hint: mines are flagged, every other cell is revealed
+    pub fn action(&self) -> GameAction {
+        match self {
+            Hint::Mine(coordinates) => GameAction::ToggleFlag(*coordinates),
+            _ => GameAction::Reveal(self.coordinates()),
+        }
+    }
+}
+
+/// Finds the next move, preferring cells the solver proves over the safest guess. Flagged cells
+/// are left alone. `mine_count` is the number of mines on the whole board, which the tile map
+/// only knows once they are placed.
+pub fn find_hint(tile_map: &TileMap, mine_count: usize) -> Option<Hint> {
+    let mut state = VisibleState::from_tile_map(tile_map);
+    state.mine_count = mine_count;
+    let flagged = |coordinates: &Coordinates| {
+        tile_map
+            .get(coordinates)
+            .is_some_and(|tile| tile.state == TileState::Flagged)
+    };
+
+    let solution = solve_state(state.clone());
+    if let Some(&safe) = solution.safe.iter().find(|cell| !flagged(cell)) {
+        return Some(Hint::Safe(safe));
+    }
+    if let Some(&mine) = solution.mines.iter().find(|cell| !flagged(cell)) {
+        return Some(Hint::Mine(mine));
+    }
+
+    probabilities_for_state(&state)
+        .cells
+        .into_iter()
+        .filter(|(coordinates, _)| !flagged(coordinates))
+        .min_by(|(a, a_probability), (b, b_probability)| {
+            a_probability.total_cmp(b_probability).then(a.cmp(b))
+        })
+        .map(|(coordinates, probability)| Hint::Guess {
+            coordinates,
+            probability,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hint(text: &str) -> Option<Hint> {
+        let tile_map: TileMap = text.parse().unwrap();
+        find_hint(&tile_map, tile_map.bomb_count as usize)
+    }
+
+    #[test]
+    fn prefers_safe_cells() {
+        let tile_map: TileMap = "5x2\n.121.\n.*.*.\n".parse().unwrap();
+        let Some(Hint::Safe(cell)) = find_hint(&tile_map, 2) else {
+            panic!("expected a safe cell");
+        };
+        assert!(!tile_map.is_bomb_at(cell));
+    }
+
+    #[test]
+    fn points_at_unflagged_mines() {
+        assert_eq!(
+            hint("2x1\n1*\n"),
+            Some(Hint::Mine(Coordinates { x: 1, y: 0 }))
+        );
+        assert_eq!(hint("2x1\n1F\n"), None);
+    }
+
+    #[test]
+    fn flags_blocking_every_move_leave_no_hint() {
+        let tile_map: TileMap = "3x1\n1Ff\n".parse().unwrap();
+        assert!(!tile_map.has_won());
+        assert_eq!(find_hint(&tile_map, 1), None);
+    }
+
+    #[test]
+    fn guesses_the_safest_cell() {
+        let Some(Hint::Guess { probability, .. }) = hint("2x2\n*2\n.*\n") else {
+            panic!("expected a guess");
+        };
+        assert!((probability - 2.0 / 3.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,28 @@
+use std::collections::HashMap;
+
+use crate::board::coordinates::Coordinates;
+
+/// Chance of every unknown cell to hold a mine, given what the player can see
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MineProbabilities {
+    pub cells: HashMap<Coordinates, f64>,
+    /// Whether every constraint group was enumerated, sampled groups give estimates
+    pub exact: bool,
+}
+
+impl MineProbabilities {
+    pub fn get(&self, coordinates: &Coordinates) -> Option<f64> {
+        self.cells.get(coordinates).copied()
+    }
+
+    /// Cell least likely to hold a mine, ties going to the lowest coordinates so the choice is
+    /// stable
+    pub fn safest(&self) -> Option<(Coordinates, f64)> {
+        self.cells
+            .iter()
+            .map(|(coordinates, probability)| (*coordinates, *probability))
+            .min_by(|(a, a_probability), (b, b_probability)| {
+                a_probability.total_cmp(b_probability).then(a.cmp(b))
+            })
+    }
+}
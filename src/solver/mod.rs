@@ -0,0 +1,12 @@
+#[cfg(all(test, feature = "bevy"))]
+mod autoplay;
+pub mod constraint;
+pub mod hint;
+pub mod mine_probabilities;
+pub mod no_guess;
+pub mod probability;
+pub mod solution;
+#[allow(clippy::module_inception)]
+pub mod solver;
+pub mod solver_step;
+pub mod visible_state;
@@ -0,0 +1,115 @@
+use rand::{Rng, seq::SliceRandom};
+
+use crate::{
+    board::{coordinates::Coordinates, no_guess_error::NoGuessError, tile_map::TileMap},
+    solver::solver::solvable_from,
+};
+
+/// Finds mine positions for a board that the solver clears from `first_click` without ever
+/// guessing, drawing new layouts until one works. `progress` is called after every layout that
+/// needs a guess, and the search gives up after `max_attempts` layouts.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_no_guess(
+    width: u16,
+    height: u16,
+    mine_count: u16,
+    excluded: &[Coordinates],
+    first_click: Coordinates,
+    max_attempts: u32,
+    rng: &mut impl Rng,
+    mut progress: impl FnMut(u32),
+) -> Result<Vec<Coordinates>, NoGuessError> {
+    let mut candidates: Vec<Coordinates> = TileMap::empty(width, height)
+        .coordinates()
+        .filter(|coordinates| *coordinates != first_click && !excluded.contains(coordinates))
+        .collect();
+
+    if (mine_count as usize) <= candidates.len() {
+        for attempt in 1..=max_attempts {
+            let (mines, _) = candidates.partial_shuffle(rng, mine_count as usize);
+            if solvable_from(&TileMap::with_mines(width, height, mines), first_click) {
+                return Ok(mines.to_vec());
+            }
+            progress(attempt);
+        }
+    }
+
+    Err(NoGuessError {
+        attempts: max_attempts,
+        width,
+        height,
+        mine_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::solver::solver::solvable_from;
+
+    #[test]
+    fn generated_boards_need_no_guess() {
+        let mut rng = ChaCha8Rng::seed_from_u64(23);
+        for (width, height, mine_count) in [(9, 9, 10), (16, 16, 40), (30, 16, 99), (9, 9, 25)] {
+            let first_click = Coordinates {
+                x: width / 2,
+                y: height / 2,
+            };
+            let excluded: Vec<Coordinates> = TileMap::empty(width, height)
+                .neighbors(first_click)
+                .collect();
+
+            for _ in 0..5 {
+                let mines = generate_no_guess(
+                    width,
+                    height,
+                    mine_count,
+                    &excluded,
+                    first_click,
+                    500,
+                    &mut rng,
+                    |_| {},
+                )
+                .unwrap();
+                assert_eq!(mines.len(), mine_count as usize);
+                assert!(!mines.iter().any(|mine| excluded.contains(mine)));
+
+                let tile_map = TileMap::with_mines(width, height, &mines);
+                assert_eq!(tile_map.bomb_count, mine_count);
+                assert!(solvable_from(&tile_map, first_click));
+            }
+        }
+    }
+
+    #[test]
+    fn gives_up_when_every_board_needs_a_guess() {
+        // The click always shows a single mine on one of its two sides
+        let mut rng = ChaCha8Rng::seed_from_u64(23);
+        let mut attempts = 0;
+        let result = generate_no_guess(
+            3,
+            1,
+            1,
+            &[],
+            Coordinates { x: 1, y: 0 },
+            50,
+            &mut rng,
+            |attempt| attempts = attempt,
+        );
+
+        assert_eq!(
+            result,
+            Err(NoGuessError {
+                attempts: 50,
+                width: 3,
+                height: 1,
+                mine_count: 1,
+            })
+        );
+        assert_eq!(attempts, 50);
+        assert!(result.unwrap_err().to_string().contains("fewer mines"));
+    }
+}
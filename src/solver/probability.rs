@@ -0,0 +1,626 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use rand::{Rng, SeedableRng, seq::SliceRandom};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    board::{coordinates::Coordinates, tile_map::TileMap},
+    solver::{mine_probabilities::MineProbabilities, visible_state::VisibleState},
+};
+
+/// Search nodes a group may use for exact enumeration before it is sampled instead
+const ENUMERATION_BUDGET: usize = 2_000_000;
+/// Layouts drawn for a group that is too large to enumerate
+const SAMPLES: usize = 5_000;
+/// Search nodes allowed to find the layout sampling starts from
+const SAMPLE_BUDGET: usize = 200_000;
+/// Cells redrawn together by one sampling step
+const SAMPLE_WINDOW: usize = 10;
+
+/// Computes the chance of every unknown cell to hold a mine from the visible numbers and the
+/// remaining mine count. The frontier is split into groups of cells linked by numbers, each
+/// group is enumerated on its own, and the groups are combined with the number of ways to
+/// place the remaining mines in the interior cells no number touches.
+pub fn mine_probabilities(tile_map: &TileMap) -> MineProbabilities {
+    probabilities_for_state(&VisibleState::from_tile_map(tile_map))
+}
+
+pub fn probabilities_for_state(state: &VisibleState) -> MineProbabilities {
+    probabilities_with_budget(state, ENUMERATION_BUDGET)
+}
+
+fn probabilities_with_budget(state: &VisibleState, budget: usize) -> MineProbabilities {
+    let remaining = state.remaining_mines();
+    let groups = Group::split(state);
+    let frontier: BTreeSet<Coordinates> = groups
+        .iter()
+        .flat_map(|group| group.cells.iter().copied())
+        .collect();
+    let interior: Vec<Coordinates> = state
+        .unknown
+        .iter()
+        .filter(|cell| !frontier.contains(cell))
+        .copied()
+        .collect();
+
+    // Weight of a frontier holding `m` mines: the number of ways to place the rest inside
+    let interior_weight = log_weights(interior.len(), remaining);
+
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let mut exact = true;
+    let counts: Vec<GroupCounts> = groups
+        .iter()
+        .map(|group| {
+            group.enumerate(remaining, budget).unwrap_or_else(|| {
+                exact = false;
+                // Cells of the other groups are counted as free cells, only their numbers are
+                // not known while sampling this group
+                let free = interior.len() + frontier.len() - group.cells.len();
+                group.sample(remaining, &log_weights(free, remaining), &mut rng)
+            })
+        })
+        .collect();
+
+    let total = convolve_all(counts.iter().map(|counts| &counts.solutions));
+    let total_weight: f64 = total
+        .iter()
+        .enumerate()
+        .map(|(mines, ways)| ways * interior_weight(mines))
+        .sum();
+
+    let mut cells = HashMap::new();
+    if total_weight <= 0.0 {
+        // The numbers contradict each other, nothing better than an even spread
+        let uniform = remaining as f64 / state.unknown.len().max(1) as f64;
+        cells.extend(state.unknown.iter().map(|cell| (*cell, uniform.min(1.0))));
+        return MineProbabilities {
+            cells,
+            exact: false,
+        };
+    }
+
+    for (index, (group, group_counts)) in groups.iter().zip(&counts).enumerate() {
+        let others = convolve_all(
+            counts
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != index)
+                .map(|(_, counts)| &counts.solutions),
+        );
+        // Weight of this group holding `k` mines, summed over every way the rest can be filled
+        let rest_weight: Vec<f64> = (0..group_counts.solutions.len())
+            .map(|k| {
+                others
+                    .iter()
+                    .enumerate()
+                    .map(|(mines, ways)| ways * interior_weight(k + mines))
+                    .sum()
+            })
+            .collect();
+
+        for (cell, cell_mines) in group.cells.iter().zip(&group_counts.cell_mines) {
+            let weight: f64 = cell_mines
+                .iter()
+                .zip(&rest_weight)
+                .map(|(ways, rest)| ways * rest)
+                .sum();
+            cells.insert(*cell, weight / total_weight);
+        }
+    }
+
+    if !interior.is_empty() {
+        let interior_mines: f64 = total
+            .iter()
+            .enumerate()
+            .map(|(mines, ways)| {
+                ways * interior_weight(mines) * remaining.saturating_sub(mines) as f64
+            })
+            .sum();
+        let probability = interior_mines / total_weight / interior.len() as f64;
+        cells.extend(interior.iter().map(|cell| (*cell, probability)));
+    }
+
+    MineProbabilities { cells, exact }
+}
+
+/// Relative number of ways to place the mines left over by a frontier holding `mines` mines in
+/// `interior` cells. Computed in log space and scaled so the largest weight is 1, as the plain
+/// binomials overflow on large boards.
+fn log_weights(interior: usize, remaining: usize) -> impl Fn(usize) -> f64 {
+    let ln_factorial: Vec<f64> = (0..=interior)
+        .scan(0.0, |sum, n| {
+            if n > 0 {
+                *sum += (n as f64).ln();
+            }
+            Some(*sum)
+        })
+        .collect();
+    let ln_binomial = move |k: usize| {
+        (k <= interior)
+            .then(|| ln_factorial[interior] - ln_factorial[k] - ln_factorial[interior - k])
+    };
+    let max = (0..=remaining.min(interior))
+        .filter_map(&ln_binomial)
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    move |mines: usize| {
+        remaining
+            .checked_sub(mines)
+            .and_then(&ln_binomial)
+            .map_or(0.0, |ln| (ln - max).exp())
+    }
+}
+
+/// Distribution of the total mine count over several groups
+fn convolve_all<'a>(distributions: impl Iterator<Item = &'a Vec<f64>>) -> Vec<f64> {
+    distributions.fold(vec![1.0], |total, distribution| {
+        let mut result = vec![0.0; total.len() + distribution.len() - 1];
+        for (a, ways_a) in total.iter().enumerate() {
+            for (b, ways_b) in distribution.iter().enumerate() {
+                result[a + b] += ways_a * ways_b;
+            }
+        }
+        result
+    })
+}
+
+/// Frontier cells linked to each other through the numbers around them
+struct Group {
+    cells: Vec<Coordinates>,
+    /// Cell indices and mine count of every number in the group
+    constraints: Vec<(Vec<usize>, usize)>,
+    /// Constraints each cell takes part in
+    cell_constraints: Vec<Vec<usize>>,
+}
+
+/// Number of valid layouts of a group by mine count, overall and for each cell being a mine
+struct GroupCounts {
+    solutions: Vec<f64>,
+    cell_mines: Vec<Vec<f64>>,
+}
+
+impl Group {
+    fn split(state: &VisibleState) -> Vec<Group> {
+        let mut by_cell: HashMap<Coordinates, Vec<usize>> = HashMap::new();
+        for (index, constraint) in state.constraints.iter().enumerate() {
+            for cell in &constraint.cells {
+                by_cell.entry(*cell).or_default().push(index);
+            }
+        }
+
+        let mut grouped = vec![false; state.constraints.len()];
+        let mut groups = Vec::new();
+        for start in 0..state.constraints.len() {
+            if grouped[start] {
+                continue;
+            }
+
+            // Breadth first over the constraints, so cells come in an order that closes the
+            // constraints early and prunes the search
+            grouped[start] = true;
+            let mut queue = VecDeque::from([start]);
+            let mut members = Vec::new();
+            let mut cells = Vec::new();
+            let mut cell_index: HashMap<Coordinates, usize> = HashMap::new();
+            while let Some(index) = queue.pop_front() {
+                members.push(index);
+                for cell in &state.constraints[index].cells {
+                    if !cell_index.contains_key(cell) {
+                        cell_index.insert(*cell, cells.len());
+                        cells.push(*cell);
+                    }
+                    for &other in &by_cell[cell] {
+                        if !grouped[other] {
+                            grouped[other] = true;
+                            queue.push_back(other);
+                        }
+                    }
+                }
+            }
+
+            let mut cell_constraints = vec![Vec::new(); cells.len()];
+            let constraints = members
+                .iter()
+                .enumerate()
+                .map(|(position, &index)| {
+                    let constraint = &state.constraints[index];
+                    let indices: Vec<usize> = constraint
+                        .cells
+                        .iter()
+                        .map(|cell| cell_index[cell])
+                        .collect();
+                    for &cell in &indices {
+                        cell_constraints[cell].push(position);
+                    }
+                    (indices, constraint.mines)
+                })
+                .collect();
+
+            groups.push(Group {
+                cells,
+                constraints,
+                cell_constraints,
+            });
+        }
+        groups
+    }
+
+    fn counts(&self) -> GroupCounts {
+        GroupCounts {
+            solutions: vec![0.0; self.cells.len() + 1],
+            cell_mines: vec![vec![0.0; self.cells.len() + 1]; self.cells.len()],
+        }
+    }
+
+    /// Counts every valid layout, or gives up once the search exceeds `budget` nodes
+    fn enumerate(&self, max_mines: usize, budget: usize) -> Option<GroupCounts> {
+        let mut search = Search::new(self, max_mines, budget);
+        let mut counts = self.counts();
+        search.enumerate(0, &mut counts).then_some(counts)
+    }
+
+    /// Estimates the layout counts when enumerating is too slow. Starting from any valid layout,
+    /// every step redraws a window of linked cells among all the ways to fill it given the rest
+    /// of the layout, which can change the number of mines in the group. Layouts are drawn in
+    /// proportion to `weight` of their mine count, the ways to place the other mines outside the
+    /// group, so the counts that matter get most samples. The weight is divided out again when
+    /// recording.
+    fn sample(
+        &self,
+        max_mines: usize,
+        weight: &impl Fn(usize) -> f64,
+        rng: &mut impl Rng,
+    ) -> GroupCounts {
+        let mut counts = self.counts();
+        let mut search = Search::new(self, max_mines, SAMPLE_BUDGET);
+        if !search.first(0, rng) {
+            return counts;
+        }
+
+        let mut fillings = Vec::new();
+        for _ in 0..SAMPLES {
+            let window = self.window(rng.random_range(0..self.cells.len()), rng);
+            for &cell in &window {
+                search.unassign(cell);
+            }
+
+            fillings.clear();
+            search.fillings(&window, 0, &mut fillings);
+            let weights: Vec<f64> = fillings.iter().map(|(_, mines)| weight(*mines)).collect();
+            let total: f64 = weights.iter().sum();
+            let mut pick = rng.random_range(0.0..1.0) * total;
+            let chosen = weights
+                .iter()
+                .position(|weight| {
+                    pick -= weight;
+                    pick < 0.0
+                })
+                .unwrap_or(0);
+
+            // The current filling is always valid, so there is at least one to pick from
+            for (&cell, &mine) in window.iter().zip(&fillings[chosen].0) {
+                search.assign(cell, mine);
+            }
+            let layout_weight = weight(search.mines);
+            if layout_weight > 0.0 {
+                counts.record(&search.assignment, search.mines, 1.0 / layout_weight);
+            }
+        }
+        counts
+    }
+
+    /// Up to `SAMPLE_WINDOW` cells linked to `start` through the numbers around them
+    fn window(&self, start: usize, rng: &mut impl Rng) -> Vec<usize> {
+        let mut window = vec![start];
+        let mut next = 0;
+        while next < window.len() && window.len() < SAMPLE_WINDOW {
+            let mut linked: Vec<usize> = self.cell_constraints[window[next]]
+                .iter()
+                .flat_map(|&constraint| self.constraints[constraint].0.iter().copied())
+                .filter(|cell| !window.contains(cell))
+                .collect();
+            linked.sort_unstable();
+            linked.dedup();
+            linked.shuffle(rng);
+            let room = SAMPLE_WINDOW - window.len();
+            window.extend(linked.into_iter().take(room));
+            next += 1;
+        }
+        window
+    }
+}
+
+impl GroupCounts {
+    fn record(&mut self, assignment: &[bool], mines: usize, amount: f64) {
+        self.solutions[mines] += amount;
+        for (cell, mine) in assignment.iter().enumerate() {
+            if *mine {
+                self.cell_mines[cell][mines] += amount;
+            }
+        }
+    }
+}
+
+/// Backtracking over the cells of a group, checking every number as cells are assigned
+struct Search<'a> {
+    group: &'a Group,
+    assignment: Vec<bool>,
+    /// Mines placed around each number so far
+    placed: Vec<usize>,
+    /// Cells around each number that are not assigned yet
+    open: Vec<usize>,
+    mines: usize,
+    max_mines: usize,
+    nodes: usize,
+    budget: usize,
+}
+
+impl<'a> Search<'a> {
+    fn new(group: &'a Group, max_mines: usize, budget: usize) -> Self {
+        Self {
+            group,
+            assignment: vec![false; group.cells.len()],
+            placed: vec![0; group.constraints.len()],
+            open: group
+                .constraints
+                .iter()
+                .map(|(cells, _)| cells.len())
+                .collect(),
+            mines: 0,
+            max_mines,
+            nodes: 0,
+            budget,
+        }
+    }
+
+    /// Assigns a cell, returning whether every number around it can still be satisfied
+    fn assign(&mut self, cell: usize, mine: bool) -> bool {
+        self.assignment[cell] = mine;
+        self.mines += mine as usize;
+        let mut feasible = self.mines <= self.max_mines;
+        for &constraint in &self.group.cell_constraints[cell] {
+            self.open[constraint] -= 1;
+            self.placed[constraint] += mine as usize;
+            let needed = self.group.constraints[constraint].1;
+            feasible &= self.placed[constraint] <= needed
+                && self.placed[constraint] + self.open[constraint] >= needed;
+        }
+        feasible
+    }
+
+    fn unassign(&mut self, cell: usize) {
+        let mine = self.assignment[cell];
+        self.mines -= mine as usize;
+        for &constraint in &self.group.cell_constraints[cell] {
+            self.open[constraint] += 1;
+            self.placed[constraint] -= mine as usize;
+        }
+        self.assignment[cell] = false;
+    }
+
+    /// Lists every valid way to fill the unassigned `window` cells from `index` on, with the
+    /// number of mines in the whole group for each
+    fn fillings(&mut self, window: &[usize], index: usize, found: &mut Vec<(Vec<bool>, usize)>) {
+        let Some(&cell) = window.get(index) else {
+            let filling = window.iter().map(|&cell| self.assignment[cell]).collect();
+            found.push((filling, self.mines));
+            return;
+        };
+
+        for mine in [false, true] {
+            if self.assign(cell, mine) {
+                self.fillings(window, index + 1, found);
+            }
+            self.unassign(cell);
+        }
+    }
+
+    /// Counts the layouts of the cells from `cell` on, returning false when out of budget
+    fn enumerate(&mut self, cell: usize, counts: &mut GroupCounts) -> bool {
+        if cell == self.group.cells.len() {
+            counts.record(&self.assignment, self.mines, 1.0);
+            return true;
+        }
+
+        for mine in [false, true] {
+            self.nodes += 1;
+            if self.nodes > self.budget {
+                return false;
+            }
+
+            let feasible = self.assign(cell, mine);
+            let within_budget = !feasible || self.enumerate(cell + 1, counts);
+            self.unassign(cell);
+            if !within_budget {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Finds one layout trying the values in a random order, leaving it in `assignment`
+    fn first(&mut self, cell: usize, rng: &mut impl Rng) -> bool {
+        if cell == self.group.cells.len() {
+            return true;
+        }
+
+        let mut values = [false, true];
+        values.shuffle(rng);
+        for mine in values {
+            self.nodes += 1;
+            if self.nodes > self.budget {
+                return false;
+            }
+
+            if self.assign(cell, mine) && self.first(cell + 1, rng) {
+                return true;
+            }
+            self.unassign(cell);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Probabilities found by trying every layout of the unknown cells
+    fn brute_force(tile_map: &TileMap) -> HashMap<Coordinates, f64> {
+        let state = VisibleState::from_tile_map(tile_map);
+        let cells: Vec<Coordinates> = state.unknown.iter().copied().collect();
+        assert!(cells.len() <= 16, "too many unknown cells to enumerate");
+
+        let mut layouts = 0.0;
+        let mut mines = vec![0.0; cells.len()];
+        for layout in 0u32..(1 << cells.len()) {
+            let is_mine = |cell: &Coordinates| {
+                let index = cells.iter().position(|other| other == cell).unwrap();
+                layout & (1 << index) != 0
+            };
+            let consistent = layout.count_ones() as usize == state.remaining_mines()
+                && state.constraints.iter().all(|constraint| {
+                    constraint.cells.iter().filter(|cell| is_mine(cell)).count() == constraint.mines
+                });
+            if consistent {
+                layouts += 1.0;
+                for (index, count) in mines.iter_mut().enumerate() {
+                    if layout & (1 << index) != 0 {
+                        *count += 1.0;
+                    }
+                }
+            }
+        }
+
+        cells
+            .into_iter()
+            .zip(mines)
+            .map(|(cell, count)| (cell, count / layouts))
+            .collect()
+    }
+
+    fn assert_close(actual: &MineProbabilities, expected: &HashMap<Coordinates, f64>, within: f64) {
+        assert_eq!(actual.cells.len(), expected.len());
+        for (cell, probability) in expected {
+            let found = actual.get(cell).unwrap();
+            assert!(
+                (found - probability).abs() <= within,
+                "{}: expected {}, found {}",
+                cell,
+                probability,
+                found
+            );
+        }
+    }
+
+    #[test]
+    fn weights_the_interior() {
+        // The 1 has a single mine among two cells, the last mine is among the four interior
+        // cells. Layouts with the frontier mine next to fewer cells are not more likely.
+        let tile_map: TileMap = "3x3\n1..\n*..\n..*\n".parse().unwrap();
+        let probabilities = mine_probabilities(&tile_map);
+
+        assert!(probabilities.exact);
+        assert_close(&probabilities, &brute_force(&tile_map), 1e-9);
+        let sum: f64 = probabilities.cells.values().sum();
+        assert!((sum - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn forced_cells_are_certain() {
+        let tile_map: TileMap = "5x2\n.121.\n.*.*.\n".parse().unwrap();
+        let probabilities = mine_probabilities(&tile_map);
+
+        assert_eq!(probabilities.get(&Coordinates { x: 1, y: 0 }), Some(1.0));
+        assert_eq!(probabilities.get(&Coordinates { x: 2, y: 0 }), Some(0.0));
+        assert_eq!(probabilities.safest().unwrap().1, 0.0);
+    }
+
+    #[test]
+    fn matches_brute_force_on_random_boards() {
+        let mut rng = ChaCha8Rng::seed_from_u64(21);
+        let mut checked = 0;
+        for _ in 0..600 {
+            let mut tile_map = TileMap::empty(5, 4);
+            tile_map.set_bombs(rng.random_range(3..=7), &[], &mut rng);
+            for _ in 0..rng.random_range(1..=4) {
+                let cell = Coordinates {
+                    x: rng.random_range(0..5),
+                    y: rng.random_range(0..4),
+                };
+                if !tile_map.is_bomb_at(cell) {
+                    tile_map.reveal_from(cell);
+                }
+            }
+
+            if VisibleState::from_tile_map(&tile_map).unknown.len() <= 16 {
+                assert_close(
+                    &mine_probabilities(&tile_map),
+                    &brute_force(&tile_map),
+                    1e-9,
+                );
+                checked += 1;
+            }
+        }
+        assert!(checked > 150, "only {} boards were checked", checked);
+    }
+
+    #[test]
+    fn samples_groups_over_budget() {
+        // The frontier holds either the middle mine, with one more among the three interior
+        // cells, or the two outer cells and nothing inside: three layouts against one
+        let tile_map: TileMap = "8x1\n.1*1..*.\n".parse().unwrap();
+        let probabilities = probabilities_with_budget(&VisibleState::from_tile_map(&tile_map), 0);
+
+        assert!(!probabilities.exact);
+        assert_close(&probabilities, &brute_force(&tile_map), 0.02);
+        assert!((probabilities.get(&Coordinates { x: 2, y: 0 }).unwrap() - 0.75).abs() < 0.02);
+    }
+
+    #[test]
+    fn sampling_matches_brute_force_on_random_boards() {
+        let mut rng = ChaCha8Rng::seed_from_u64(121);
+        let mut checked = 0;
+        while checked < 50 {
+            let mut tile_map = TileMap::empty(6, 4);
+            tile_map.set_bombs(rng.random_range(3..=8), &[], &mut rng);
+            let cell = Coordinates {
+                x: rng.random_range(0..6),
+                y: rng.random_range(0..4),
+            };
+            if tile_map.is_bomb_at(cell) {
+                continue;
+            }
+            tile_map.reveal_from(cell);
+
+            let state = VisibleState::from_tile_map(&tile_map);
+            if state.unknown.len() <= 16 && !state.constraints.is_empty() {
+                assert_close(
+                    &probabilities_with_budget(&state, 0),
+                    &brute_force(&tile_map),
+                    0.05,
+                );
+                checked += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn handles_large_boards() {
+        let mut tile_map = TileMap::empty(30, 16);
+        let mut rng = ChaCha8Rng::seed_from_u64(99);
+        let center = Coordinates { x: 15, y: 8 };
+        let mut excluded: Vec<Coordinates> = tile_map.neighbors(center).collect();
+        excluded.push(center);
+        tile_map.set_bombs(99, &excluded, &mut rng);
+        tile_map.reveal_from(center);
+
+        let probabilities = mine_probabilities(&tile_map);
+        let sum: f64 = probabilities.cells.values().sum();
+        assert!(
+            (sum - 99.0).abs() < 1e-6,
+            "expected 99 mines, found {}",
+            sum
+        );
+    }
+}
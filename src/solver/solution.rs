@@ -0,0 +1,15 @@
+use crate::{board::coordinates::Coordinates, solver::solver_step::SolverStep};
+
+/// Cells the solver proved, in the order it found them
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Solution {
+    pub safe: Vec<Coordinates>,
+    pub mines: Vec<Coordinates>,
+    pub steps: Vec<SolverStep>,
+}
+
+impl Solution {
+    pub fn is_empty(&self) -> bool {
+        self.safe.is_empty() && self.mines.is_empty()
+    }
+}
@@ -0,0 +1,433 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+    board::{coordinates::Coordinates, tile_map::TileMap},
+    solver::{
+        constraint::Constraint,
+        solution::Solution,
+        solver_step::{SolverRule, SolverStep},
+        visible_state::VisibleState,
+    },
+};
+
+/// Finds the cells that are proven safe or proven to be mines from what the player can see of
+/// the board, without ever looking at hidden tiles. Cheaper rules are tried first and the
+/// search restarts from them after every deduction.
+pub fn solve(tile_map: &TileMap) -> Solution {
+    solve_state(VisibleState::from_tile_map(tile_map))
+}
+
+/// Whether the board can be cleared from `first_click` by revealing the cells the solver proves
+/// safe, without ever guessing. Flags on the board are ignored.
+pub fn solvable_from(tile_map: &TileMap, first_click: Coordinates) -> bool {
+    if tile_map.is_bomb_at(first_click) {
+        return false;
+    }
+
+    let mines: Vec<Coordinates> = tile_map
+        .coordinates()
+        .filter(|&coordinates| tile_map.is_bomb_at(coordinates))
+        .collect();
+    let mut tile_map = TileMap::with_mines(tile_map.width(), tile_map.height(), &mines);
+    tile_map.reveal_from(first_click);
+    while tile_map.counts().hidden_safe > 0 {
+        let solution = solve(&tile_map);
+        if solution.safe.is_empty() {
+            return false;
+        }
+        for cell in solution.safe {
+            tile_map.reveal_from(cell);
+        }
+    }
+    true
+}
+
+pub fn solve_state(state: VisibleState) -> Solution {
+    let mut solver = Solver {
+        state,
+        known: HashMap::new(),
+        found_mines: 0,
+        solution: Solution::default(),
+    };
+    while solver.single_cell() || solver.pairs() || solver.global_count() {}
+    solver.solution
+}
+
+struct Solver {
+    state: VisibleState,
+    /// Cells proven so far, `true` for mines
+    known: HashMap<Coordinates, bool>,
+    found_mines: usize,
+    solution: Solution,
+}
+
+impl Solver {
+    /// Records a deduction, returning whether it proved any new cell
+    fn apply(
+        &mut self,
+        rule: SolverRule,
+        sources: Vec<Coordinates>,
+        safe: impl IntoIterator<Item = Coordinates>,
+        mines: impl IntoIterator<Item = Coordinates>,
+    ) -> bool {
+        let safe: Vec<Coordinates> = safe
+            .into_iter()
+            .filter(|cell| !self.known.contains_key(cell))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let mines: Vec<Coordinates> = mines
+            .into_iter()
+            .filter(|cell| !self.known.contains_key(cell))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if safe.is_empty() && mines.is_empty() {
+            return false;
+        }
+
+        for (cell, mine) in safe
+            .iter()
+            .map(|cell| (*cell, false))
+            .chain(mines.iter().map(|cell| (*cell, true)))
+        {
+            self.known.insert(cell, mine);
+            for constraint in &mut self.state.constraints {
+                constraint.resolve(cell, mine);
+            }
+        }
+        self.found_mines += mines.len();
+        self.state
+            .constraints
+            .retain(|constraint| !constraint.cells.is_empty());
+
+        self.solution.safe.extend(&safe);
+        self.solution.mines.extend(&mines);
+        self.solution.steps.push(SolverStep {
+            rule,
+            sources,
+            safe,
+            mines,
+        });
+        true
+    }
+
+    fn single_cell(&mut self) -> bool {
+        let Some(constraint) = self
+            .state
+            .constraints
+            .iter()
+            .find(|constraint| constraint.mines == 0 || constraint.mines == constraint.cells.len())
+            .cloned()
+        else {
+            return false;
+        };
+
+        let cells = constraint.cells.iter().copied();
+        if constraint.mines == 0 {
+            self.apply(SolverRule::SingleCell, vec![constraint.source], cells, [])
+        } else {
+            self.apply(SolverRule::SingleCell, vec![constraint.source], [], cells)
+        }
+    }
+
+    fn pairs(&mut self) -> bool {
+        let constraints = &self.state.constraints;
+        let mut found = None;
+        'search: for (index, a) in constraints.iter().enumerate() {
+            for b in &constraints[index + 1..] {
+                if !a.overlaps(b) {
+                    continue;
+                }
+
+                for (a, b) in [(a, b), (b, a)] {
+                    let (safe, mines) = Self::compare(a, b);
+                    if !safe.is_empty() || !mines.is_empty() {
+                        let rule = if a.is_subset_of(b) {
+                            SolverRule::Subset
+                        } else {
+                            SolverRule::Overlap
+                        };
+                        found = Some((rule, vec![a.source, b.source], safe, mines));
+                        break 'search;
+                    }
+                }
+            }
+        }
+
+        found.is_some_and(|(rule, sources, safe, mines)| self.apply(rule, sources, safe, mines))
+    }
+
+    /// Proves the cells only `b` sees, from the number of mines the cells shared with `a` can
+    /// hold. Returns the safe cells and the mines.
+    fn compare(a: &Constraint, b: &Constraint) -> (Vec<Coordinates>, Vec<Coordinates>) {
+        let only_b: Vec<Coordinates> = b.cells.difference(&a.cells).copied().collect();
+        if only_b.is_empty() {
+            return (Vec::new(), Vec::new());
+        }
+
+        let shared = a.cells.intersection(&b.cells).count();
+        let only_a = a.cells.len() - shared;
+        let max_shared = shared.min(a.mines).min(b.mines);
+        let min_shared = a
+            .mines
+            .saturating_sub(only_a)
+            .max(b.mines.saturating_sub(only_b.len()));
+
+        if b.mines.saturating_sub(max_shared) == only_b.len() {
+            (Vec::new(), only_b)
+        } else if b.mines <= min_shared {
+            (only_b, Vec::new())
+        } else {
+            (Vec::new(), Vec::new())
+        }
+    }
+
+    fn global_count(&mut self) -> bool {
+        let unknown: Vec<Coordinates> = self
+            .state
+            .unknown
+            .iter()
+            .filter(|cell| !self.known.contains_key(cell))
+            .copied()
+            .collect();
+        if unknown.is_empty() {
+            return false;
+        }
+
+        let remaining = self
+            .state
+            .remaining_mines()
+            .saturating_sub(self.found_mines);
+        if remaining == 0 {
+            return self.apply(SolverRule::GlobalCount, Vec::new(), unknown, []);
+        }
+        if remaining == unknown.len() {
+            return self.apply(SolverRule::GlobalCount, Vec::new(), [], unknown);
+        }
+
+        // Numbers that share no cells need at least the sum of their mines, when that is every
+        // remaining mine the cells they do not see are safe
+        let mut covered = BTreeSet::new();
+        let mut sources = Vec::new();
+        let mut mines = 0;
+        for constraint in &self.state.constraints {
+            if constraint.cells.is_disjoint(&covered) {
+                covered.extend(constraint.cells.iter().copied());
+                sources.push(constraint.source);
+                mines += constraint.mines;
+            }
+        }
+        if mines != remaining {
+            return false;
+        }
+
+        let safe = unknown.into_iter().filter(|cell| !covered.contains(cell));
+        self.apply(SolverRule::GlobalCount, sources, safe, [])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    /// Cells that hold the same content in every mine layout matching the visible state, found
+    /// by trying every layout
+    fn brute_force(tile_map: &TileMap) -> (BTreeSet<Coordinates>, BTreeSet<Coordinates>) {
+        let state = VisibleState::from_tile_map(tile_map);
+        let cells: Vec<Coordinates> = state.unknown.iter().copied().collect();
+        assert!(cells.len() <= 16, "too many unknown cells to enumerate");
+
+        let mut always_safe = (1u32 << cells.len()) - 1;
+        let mut always_mine = always_safe;
+        for layout in 0u32..(1 << cells.len()) {
+            let is_mine = |cell: &Coordinates| {
+                let index = cells.iter().position(|other| other == cell).unwrap();
+                layout & (1 << index) != 0
+            };
+            let consistent = layout.count_ones() as usize == state.remaining_mines()
+                && state.constraints.iter().all(|constraint| {
+                    constraint.cells.iter().filter(|cell| is_mine(cell)).count() == constraint.mines
+                });
+            if consistent {
+                always_safe &= !layout;
+                always_mine &= layout;
+            }
+        }
+
+        let select = |mask: u32| {
+            cells
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| mask & (1 << index) != 0)
+                .map(|(_, cell)| *cell)
+                .collect()
+        };
+        (select(always_safe), select(always_mine))
+    }
+
+    fn assert_sound(tile_map: &TileMap, solution: &Solution) {
+        for cell in &solution.safe {
+            assert!(!tile_map.is_bomb_at(*cell), "{} is a mine", cell);
+        }
+        for cell in &solution.mines {
+            assert!(tile_map.is_bomb_at(*cell), "{} is safe", cell);
+        }
+    }
+
+    /// The solver only proves cells that are the same in every matching layout
+    fn assert_within_brute_force(tile_map: &TileMap) {
+        let solution = solve(tile_map);
+        assert_sound(tile_map, &solution);
+
+        let (safe, mines) = brute_force(tile_map);
+        assert!(solution.safe.iter().all(|cell| safe.contains(cell)));
+        assert!(solution.mines.iter().all(|cell| mines.contains(cell)));
+    }
+
+    /// The solver proves every cell that can be proven
+    fn assert_complete(tile_map: &TileMap) {
+        let solution = solve(tile_map);
+        assert_sound(tile_map, &solution);
+
+        let (safe, mines) = brute_force(tile_map);
+        assert_eq!(solution.safe.iter().copied().collect::<BTreeSet<_>>(), safe);
+        assert_eq!(
+            solution.mines.iter().copied().collect::<BTreeSet<_>>(),
+            mines
+        );
+    }
+
+    /// The board in all eight rotations and reflections, in the text format
+    fn orientations(rows: &[&str]) -> Vec<String> {
+        let mut grid: Vec<Vec<char>> = rows.iter().map(|row| row.chars().collect()).collect();
+        let mut boards = Vec::new();
+        for _ in 0..4 {
+            let mirrored: Vec<Vec<char>> = grid
+                .iter()
+                .map(|row| row.iter().rev().copied().collect())
+                .collect();
+            for grid in [&grid, &mirrored] {
+                let mut text = format!("{}x{}\n", grid[0].len(), grid.len());
+                for row in grid {
+                    text.extend(row);
+                    text.push('\n');
+                }
+                boards.push(text);
+            }
+
+            grid = (0..grid[0].len())
+                .map(|x| grid.iter().rev().map(|row| row[x]).collect())
+                .collect();
+        }
+        boards
+    }
+
+    fn cells(cells: &[(u16, u16)]) -> BTreeSet<Coordinates> {
+        cells.iter().map(|&(x, y)| Coordinates { x, y }).collect()
+    }
+
+    #[test]
+    fn solves_1_2_1() {
+        let tile_map: TileMap = "5x2\n.121.\n.*.*.\n".parse().unwrap();
+        let solution = solve(&tile_map);
+
+        assert_eq!(
+            solution.mines.iter().copied().collect::<BTreeSet<_>>(),
+            cells(&[(1, 0), (3, 0)])
+        );
+        assert_eq!(
+            solution.safe.iter().copied().collect::<BTreeSet<_>>(),
+            cells(&[(0, 0), (0, 1), (2, 0), (4, 0), (4, 1)])
+        );
+        assert_eq!(solution.steps[0].rule, SolverRule::Overlap);
+
+        for board in orientations(&[".121.", ".*.*."]) {
+            assert_complete(&board.parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn solves_1_2_2_1() {
+        let tile_map: TileMap = "6x2\n.1221.\n..**..\n".parse().unwrap();
+        let solution = solve(&tile_map);
+
+        assert_eq!(
+            solution.mines.iter().copied().collect::<BTreeSet<_>>(),
+            cells(&[(2, 0), (3, 0)])
+        );
+        assert_eq!(solution.safe.len(), 6);
+
+        for board in orientations(&[".1221.", "..**.."]) {
+            assert_complete(&board.parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn subset_proves_the_difference() {
+        // The 1 at the wall sees two of the three cells around the 2 next to it
+        let tile_map: TileMap = "3x2\n121\n*.*\n".parse().unwrap();
+        let solution = solve(&tile_map);
+
+        assert_eq!(solution.steps[0].rule, SolverRule::Subset);
+        assert_eq!(solution.steps[0].mines, vec![Coordinates { x: 2, y: 0 }]);
+        assert_complete(&tile_map);
+    }
+
+    #[test]
+    fn global_count_clears_the_rest() {
+        let tile_map: TileMap = "4x1\n1*..\n".parse().unwrap();
+        let solution = solve(&tile_map);
+
+        assert_eq!(solution.mines, vec![Coordinates { x: 1, y: 0 }]);
+        assert_eq!(solution.steps[1].rule, SolverRule::GlobalCount);
+        assert_eq!(solution.safe.len(), 2);
+    }
+
+    #[test]
+    fn ignores_player_flags() {
+        let tile_map: TileMap = "3x1\nf1*\n".parse().unwrap();
+        let solution = solve(&tile_map);
+
+        assert!(solution.is_empty());
+        assert_complete(&tile_map);
+    }
+
+    #[test]
+    fn solvable_only_without_guesses() {
+        let solvable: TileMap = "4x1\n..*.\n".parse().unwrap();
+        assert!(solvable_from(&solvable, Coordinates { x: 0, y: 0 }));
+        assert!(!solvable_from(&solvable, Coordinates { x: 2, y: 0 }));
+
+        let fifty_fifty: TileMap = "2x2\n*.\n.*\n".parse().unwrap();
+        assert!(!solvable_from(&fifty_fifty, Coordinates { x: 1, y: 0 }));
+    }
+
+    #[test]
+    fn agrees_with_brute_force_on_random_boards() {
+        let mut rng = ChaCha8Rng::seed_from_u64(20);
+        let mut checked = 0;
+        for _ in 0..400 {
+            let mut tile_map = TileMap::empty(5, 4);
+            tile_map.set_bombs(rng.random_range(3..=7), &[], &mut rng);
+            for _ in 0..rng.random_range(1..=4) {
+                let cell = Coordinates {
+                    x: rng.random_range(0..5),
+                    y: rng.random_range(0..4),
+                };
+                if !tile_map.is_bomb_at(cell) {
+                    tile_map.reveal_from(cell);
+                }
+            }
+
+            if VisibleState::from_tile_map(&tile_map).unknown.len() <= 16 {
+                assert_within_brute_force(&tile_map);
+                checked += 1;
+            }
+        }
+        assert!(checked > 100, "only {} boards were checked", checked);
+    }
+}
@@ -0,0 +1,51 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::board::coordinates::Coordinates;
+
+/// Rule used by the solver to prove cells
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolverRule {
+    /// A number whose unknown neighbors are all mines, or all safe
+    SingleCell,
+    /// The cells of one number are all around another, so the difference holds the difference
+    /// of the two numbers
+    Subset,
+    /// Two numbers sharing cells bound how many mines the shared cells hold, proving the cells
+    /// only one of them sees
+    Overlap,
+    /// The remaining mine count accounts for every mine, or for none of them
+    GlobalCount,
+}
+
+/// One deduction, with the numbers it was made from and the cells it proved
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolverStep {
+    pub rule: SolverRule,
+    pub sources: Vec<Coordinates>,
+    pub safe: Vec<Coordinates>,
+    pub mines: Vec<Coordinates>,
+}
+
+impl Display for SolverStep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let list = |cells: &[Coordinates]| {
+            cells
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        write!(f, "{:?}", self.rule)?;
+        if !self.sources.is_empty() {
+            write!(f, " from {}", list(&self.sources))?;
+        }
+        if !self.safe.is_empty() {
+            write!(f, ", safe: {}", list(&self.safe))?;
+        }
+        if !self.mines.is_empty() {
+            write!(f, ", mines: {}", list(&self.mines))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    board::{
+        coordinates::Coordinates,
+        tile::{tile_state::TileState, tile_type::TileType},
+        tile_map::TileMap,
+    },
+    solver::constraint::Constraint,
+};
+
+/// What the player can see of a board: the revealed numbers, the cells that are still unknown
+/// and the total number of mines. Flags and question marks are the player's guesses, so flagged
+/// cells are unknown like any hidden cell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VisibleState {
+    pub unknown: BTreeSet<Coordinates>,
+    /// Mines already shown on the board, only after a loss
+    pub known_mines: usize,
+    pub mine_count: usize,
+    /// One constraint per revealed number next to unknown cells
+    pub constraints: Vec<Constraint>,
+}
+
+impl VisibleState {
+    pub fn from_tile_map(tile_map: &TileMap) -> Self {
+        let mut unknown = BTreeSet::new();
+        let mut known_mines = 0;
+        for (coordinates, tile) in tile_map.iter() {
+            match (tile.state, tile.r#type) {
+                (TileState::Revealed | TileState::Exploded, TileType::Bomb) => known_mines += 1,
+                (TileState::Revealed, _) => {}
+                _ => {
+                    unknown.insert(coordinates);
+                }
+            }
+        }
+
+        let constraints = tile_map
+            .iter()
+            .filter_map(|(source, tile)| {
+                let TileType::Neighbour(number) = tile.r#type else {
+                    return None;
+                };
+                if tile.state != TileState::Revealed {
+                    return None;
+                }
+
+                let mut mines = number as usize;
+                let mut cells = BTreeSet::new();
+                for neighbor in tile_map.neighbors(source) {
+                    if unknown.contains(&neighbor) {
+                        cells.insert(neighbor);
+                    } else if tile_map.is_bomb_at(neighbor) {
+                        mines = mines.saturating_sub(1);
+                    }
+                }
+
+                (!cells.is_empty()).then_some(Constraint {
+                    source,
+                    cells,
+                    mines,
+                })
+            })
+            .collect();
+
+        Self {
+            unknown,
+            known_mines,
+            mine_count: tile_map.bomb_count as usize,
+            constraints,
+        }
+    }
+
+    /// Mines that are not shown on the board yet
+    pub fn remaining_mines(&self) -> usize {
+        self.mine_count.saturating_sub(self.known_mines)
+    }
+}
@@ -0,0 +1,5 @@
+use bevy::ecs::resource::Resource;
+
+/// Error shown on the main menu, for example when the chosen board can not be generated
+#[derive(Resource)]
+pub struct MenuError(pub String);
@@ -1,3 +1,5 @@
 pub mod menu_data;
+pub mod menu_error;
+pub mod no_guess_mode;
 pub mod settings;
 pub mod ui_plugin;
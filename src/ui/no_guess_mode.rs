@@ -0,0 +1,7 @@
+use bevy::prelude::*;
+
+/// Whether boards started from the main menu are generated so they never need a guess
+#[derive(Resource, Default)]
+pub struct NoGuessMode {
+    pub enabled: bool,
+}
@@ -4,6 +4,8 @@ use bevy::prelude::*;
 pub struct UiSettings {
     pub tile_size: f32,
     pub tile_spacing: f32,
+    /// Mouse gesture that chords a revealed number
+    pub chord_trigger: ChordTrigger,
 }
 
 impl Default for UiSettings {
@@ -11,6 +13,18 @@ impl Default for UiSettings {
         Self {
             tile_size: 32.0,
             tile_spacing: 0.0,
+            chord_trigger: ChordTrigger::default(),
         }
     }
 }
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ChordTrigger {
+    /// Left clicking a revealed number
+    #[default]
+    LeftClick,
+    /// Middle clicking a revealed number
+    MiddleClick,
+    /// Pressing the left and right buttons together on a revealed number
+    BothButtons,
+}
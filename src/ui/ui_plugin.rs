@@ -1,12 +1,41 @@
-use bevy::{log, prelude::*, state::commands};
+use bevy::{log, prelude::*};
 
-use crate::{board::settings::BoardSettings, ui::menu_data::MenuData, utils::app_state::AppState};
+use crate::{
+    assist::autoplay_plugin::Autoplay,
+    board::{board::Board, preset_board::PresetBoard, rules::GameRules, settings::BoardSettings},
+    replay::{replay_player::ReplayPlayer, replay_plugin::ReplayPlugin},
+    save::save_plugin::{ResumedGame, SavePlugin},
+    ui::{menu_error::MenuError, no_guess_mode::NoGuessMode},
+    utils::app_state::AppState,
+};
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_systems(Update, Self::menu)
+        app.init_resource::<NoGuessMode>()
+            .add_systems(Update, Self::menu)
+            .add_systems(
+                Update,
+                Self::show_menu_error.run_if(
+                    in_state(AppState::MainMenu).and(resource_exists_and_changed::<MenuError>),
+                ),
+            )
+            .add_systems(
+                Update,
+                Self::update_rule_labels
+                    .run_if(in_state(AppState::MainMenu).and(resource_changed::<GameRules>)),
+            )
+            .add_systems(
+                Update,
+                Self::update_no_guess_label
+                    .run_if(in_state(AppState::MainMenu).and(resource_changed::<NoGuessMode>)),
+            )
+            .add_systems(
+                Update,
+                Self::update_autoplay_label
+                    .run_if(in_state(AppState::MainMenu).and(resource_changed::<Autoplay>)),
+            )
             .add_systems(OnEnter(AppState::MainMenu), Self::setup_main_menu)
             .add_systems(OnExit(AppState::MainMenu), Self::cleanup_menu)
             .add_systems(OnExit(AppState::Defeat), Self::cleanup_menu)
@@ -19,15 +48,40 @@ impl Plugin for UiPlugin {
 #[derive(Component)]
 pub struct MenuRoot;
 
+#[derive(Component)]
+pub struct MenuErrorText;
+
+/// Text of the button letting the bot play
+#[derive(Component)]
+pub struct AutoplayLabel;
+
+/// Text of the button generating boards without guesses
+#[derive(Component)]
+pub struct NoGuessLabel;
+
+/// Text of a button toggling one of the `GameRules`
+#[derive(Component, Clone, Copy)]
+pub enum RuleLabel {
+    QuestionMarks,
+    StrictFlags,
+}
+
 #[derive(Component, PartialEq, Eq, Debug)]
 pub enum ButtonType {
+    Continue,
     Easy,
     Medium,
     Hard,
+    Replay,
+    QuestionMarks,
+    StrictFlags,
+    NoGuess,
+    Autoplay,
     MainMenu,
 }
 
 impl UiPlugin {
+    #[allow(clippy::type_complexity)]
     pub fn menu(
         mut next_state: ResMut<NextState<AppState>>,
         current_state: Res<State<AppState>>,
@@ -35,44 +89,92 @@ impl UiPlugin {
             (&Interaction, &ButtonType),
             (Changed<Interaction>, With<Button>),
         >,
+        mut rules: ResMut<GameRules>,
+        mut no_guess: ResMut<NoGuessMode>,
+        mut autoplay: ResMut<Autoplay>,
         mut commands: Commands,
     ) {
         for (interaction, button_type) in &mut interaction_query {
             match *interaction {
                 Interaction::Pressed => match current_state.get() {
+                    AppState::MainMenu if *button_type == ButtonType::Continue => {
+                        match SavePlugin::load() {
+                            Ok(save) => {
+                                log::info!("Continuing saved game");
+                                commands.remove_resource::<MenuError>();
+                                commands.insert_resource(save.settings.clone());
+                                commands.insert_resource(ResumedGame(save));
+                                next_state.set(AppState::InGame);
+                            }
+                            Err(error) => {
+                                log::error!("Failed to load saved game: {}", error);
+                                commands.insert_resource(MenuError(error.to_string()));
+                            }
+                        }
+                    }
+                    AppState::MainMenu if *button_type == ButtonType::QuestionMarks => {
+                        rules.question_marks = !rules.question_marks;
+                        log::info!("Question marks: {}", rules.question_marks);
+                    }
+                    AppState::MainMenu if *button_type == ButtonType::StrictFlags => {
+                        rules.strict_flags = !rules.strict_flags;
+                        log::info!("Strict flags: {}", rules.strict_flags);
+                    }
+                    AppState::MainMenu if *button_type == ButtonType::NoGuess => {
+                        no_guess.enabled = !no_guess.enabled;
+                        log::info!("No guess: {}", no_guess.enabled);
+                    }
+                    AppState::MainMenu if *button_type == ButtonType::Autoplay => {
+                        autoplay.enabled = !autoplay.enabled;
+                        log::info!("Autoplay: {}", autoplay.enabled);
+                    }
+                    AppState::MainMenu if *button_type == ButtonType::Replay => {
+                        match ReplayPlugin::load_latest() {
+                            Ok(replay) => {
+                                log::info!("Playing back replay from {}", replay.recorded_at);
+                                commands.remove_resource::<MenuError>();
+                                commands.insert_resource(replay.settings.clone());
+                                if let Some(preset) = replay.preset.clone() {
+                                    commands.insert_resource(PresetBoard(preset));
+                                }
+                                commands.insert_resource(ReplayPlayer::new(replay));
+                                next_state.set(AppState::Replay);
+                            }
+                            Err(error) => {
+                                log::error!("Failed to load replay: {}", error);
+                                commands.insert_resource(MenuError(error.to_string()));
+                            }
+                        }
+                    }
                     AppState::MainMenu => {
                         log::info!(
                             "Starting game from main menu with difficulty: {:?}",
                             button_type
                         );
-                        match button_type {
-                            ButtonType::Easy => {
-                                commands.insert_resource(BoardSettings {
-                                    board_width: 9,
-                                    board_height: 9,
-                                    mine_count: 10,
-                                });
-                            }
-                            ButtonType::Medium => {
-                                commands.insert_resource(BoardSettings {
-                                    board_width: 16,
-                                    board_height: 16,
-                                    mine_count: 40,
-                                });
+                        let settings = match button_type {
+                            ButtonType::Easy => BoardSettings::new(9, 9, 10),
+                            ButtonType::Medium => BoardSettings::new(16, 16, 40),
+                            ButtonType::Hard => BoardSettings::new(30, 16, 99),
+                            _ => continue,
+                        };
+
+                        match settings {
+                            Ok(settings) => {
+                                commands.remove_resource::<MenuError>();
+                                commands.insert_resource(
+                                    settings.with_rules(*rules).with_no_guess(no_guess.enabled),
+                                );
                             }
-                            ButtonType::Hard => {
-                                commands.insert_resource(BoardSettings {
-                                    board_width: 30,
-                                    board_height: 16,
-                                    mine_count: 99,
-                                });
+                            Err(error) => {
+                                log::error!("Invalid board settings: {}", error);
+                                commands.insert_resource(MenuError(error.to_string()));
+                                continue;
                             }
-                            _ => {}
                         }
 
                         next_state.set(AppState::InGame);
                     }
-                    AppState::InGame => {}
+                    AppState::InGame | AppState::Replay => {}
                     AppState::Victory => {
                         log::info!("Restarting game from victory menu");
                         next_state.set(AppState::MainMenu);
@@ -88,14 +190,15 @@ impl UiPlugin {
         }
     }
 
-    pub fn cleanup_menu(mut commands: Commands, menu_data: Query<Entity, With<MenuRoot>>) {
-        log::info!("Cleaning up menu");
-        for entity in &menu_data {
+    pub fn show_menu_error(
+        mut commands: Commands,
+        menu_error: Res<MenuError>,
+        error_texts: Query<Entity, With<MenuErrorText>>,
+    ) {
+        for entity in &error_texts {
             commands.entity(entity).despawn();
         }
-    }
 
-    pub fn setup_victory_menu(mut commands: Commands) {
         commands.spawn((
             Node {
                 width: Val::Percent(100.),
@@ -105,15 +208,109 @@ impl UiPlugin {
                 ..default()
             },
             MenuRoot,
+            MenuErrorText,
             children![(
-                Text::new("Victory!"),
+                Text::new(menu_error.0.clone()),
                 TextFont {
-                    font_size: 50.0,
+                    font_size: 24.0,
                     ..default()
                 },
-                TextColor(Color::srgb(0.1, 0.9, 0.1)),
+                TextColor(Color::srgb(0.9, 0.1, 0.1)),
             )],
         ));
+    }
+
+    fn rule_label(rules: &GameRules, label: RuleLabel) -> String {
+        let (name, enabled) = match label {
+            RuleLabel::QuestionMarks => ("? marks", rules.question_marks),
+            RuleLabel::StrictFlags => ("Strict flags", rules.strict_flags),
+        };
+        format!("{}: {}", name, if enabled { "on" } else { "off" })
+    }
+
+    fn no_guess_label(no_guess: &NoGuessMode) -> String {
+        format!("No guess: {}", if no_guess.enabled { "on" } else { "off" })
+    }
+
+    pub fn update_no_guess_label(
+        no_guess: Res<NoGuessMode>,
+        mut labels: Query<&mut Text, With<NoGuessLabel>>,
+    ) {
+        for mut text in &mut labels {
+            text.0 = Self::no_guess_label(&no_guess);
+        }
+    }
+
+    fn autoplay_label(autoplay: &Autoplay) -> String {
+        format!("Autoplay: {}", if autoplay.enabled { "on" } else { "off" })
+    }
+
+    pub fn update_autoplay_label(
+        autoplay: Res<Autoplay>,
+        mut labels: Query<&mut Text, With<AutoplayLabel>>,
+    ) {
+        for mut text in &mut labels {
+            text.0 = Self::autoplay_label(&autoplay);
+        }
+    }
+
+    pub fn update_rule_labels(rules: Res<GameRules>, mut labels: Query<(&mut Text, &RuleLabel)>) {
+        for (mut text, label) in &mut labels {
+            text.0 = Self::rule_label(&rules, *label);
+        }
+    }
+
+    pub fn cleanup_menu(mut commands: Commands, menu_data: Query<Entity, With<MenuRoot>>) {
+        log::info!("Cleaning up menu");
+        for entity in &menu_data {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    pub fn setup_victory_menu(mut commands: Commands, board: Single<&Board>) {
+        let title = if board.assisted {
+            "Victory! (assisted)"
+        } else {
+            "Victory!"
+        };
+
+        commands.spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                justify_content: JustifyContent::Start,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            MenuRoot,
+            children![
+                (
+                    Text::new(title),
+                    TextFont {
+                        font_size: 50.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.1, 0.9, 0.1)),
+                ),
+                (
+                    Text::new(format!("Hints used: {}", board.actions.hints)),
+                    TextFont {
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                ),
+                (
+                    Text::new(format!("Seed: {}", board.seed)),
+                    TextFont {
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                ),
+            ],
+        ));
 
         commands.spawn((
             Node {
@@ -147,24 +344,35 @@ impl UiPlugin {
         ));
     }
 
-    pub fn setup_defeat_menu(mut commands: Commands) {
+    pub fn setup_defeat_menu(mut commands: Commands, board: Single<&Board>) {
         commands.spawn((
             Node {
                 width: Val::Percent(100.),
                 height: Val::Percent(100.),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Start,
+                justify_content: JustifyContent::Start,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
                 ..default()
             },
             MenuRoot,
-            children![(
-                Text::new("Defeat!"),
-                TextFont {
-                    font_size: 50.0,
-                    ..default()
-                },
-                TextColor(Color::srgb(0.9, 0.1, 0.1)),
-            )],
+            children![
+                (
+                    Text::new("Defeat!"),
+                    TextFont {
+                        font_size: 50.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.9, 0.1, 0.1)),
+                ),
+                (
+                    Text::new(format!("Seed: {}", board.seed)),
+                    TextFont {
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                ),
+            ],
         ));
 
         commands.spawn((
@@ -199,63 +407,199 @@ impl UiPlugin {
         ));
     }
 
-    pub fn setup_main_menu(mut commands: Commands) {
-        commands.spawn((
-            Node {
-                width: Val::Percent(100.),
-                height: Val::Percent(100.),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                flex_direction: FlexDirection::Column,
-                display: Display::Flex,
-                row_gap: Val::Px(20.),
-                ..default()
-            },
-            MenuRoot,
-            children![
-                (
-                    Button,
-                    ButtonType::Easy,
-                    Node {
-                        width: Val::Px(150.),
-                        height: Val::Px(65.),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
-                        ..default()
-                    },
-                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
-                    children![(
-                        Text::new("Easy"),
-                        TextFont {
-                            font_size: 33.0,
+    pub fn setup_main_menu(
+        mut commands: Commands,
+        rules: Res<GameRules>,
+        no_guess: Res<NoGuessMode>,
+        autoplay: Res<Autoplay>,
+    ) {
+        let root = commands
+            .spawn((
+                Node {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Column,
+                    display: Display::Flex,
+                    row_gap: Val::Px(10.),
+                    ..default()
+                },
+                MenuRoot,
+                children![
+                    (
+                        Button,
+                        ButtonType::Easy,
+                        Node {
+                            width: Val::Px(150.),
+                            height: Val::Px(65.),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
                             ..default()
                         },
-                        TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                    )]
-                ),
-                (
-                    Button,
-                    ButtonType::Medium,
-                    Node {
-                        width: Val::Px(150.),
-                        height: Val::Px(65.),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
-                        ..default()
-                    },
-                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
-                    children![(
-                        Text::new("Medium"),
-                        TextFont {
-                            font_size: 33.0,
+                        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                        children![(
+                            Text::new("Easy"),
+                            TextFont {
+                                font_size: 33.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                        )]
+                    ),
+                    (
+                        Button,
+                        ButtonType::Medium,
+                        Node {
+                            width: Val::Px(150.),
+                            height: Val::Px(65.),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
                             ..default()
                         },
-                        TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                    )]
-                ),
-                (
+                        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                        children![(
+                            Text::new("Medium"),
+                            TextFont {
+                                font_size: 33.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                        )]
+                    ),
+                    (
+                        Button,
+                        ButtonType::Hard,
+                        Node {
+                            width: Val::Px(150.),
+                            height: Val::Px(65.),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                        children![(
+                            Text::new("Hard"),
+                            TextFont {
+                                font_size: 33.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                        )]
+                    ),
+                    (
+                        Button,
+                        ButtonType::Replay,
+                        Node {
+                            width: Val::Px(150.),
+                            height: Val::Px(65.),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                        children![(
+                            Text::new("Replay"),
+                            TextFont {
+                                font_size: 33.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                        )]
+                    ),
+                    (
+                        Button,
+                        ButtonType::QuestionMarks,
+                        Node {
+                            width: Val::Px(150.),
+                            height: Val::Px(65.),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                        children![(
+                            Text::new(Self::rule_label(&rules, RuleLabel::QuestionMarks)),
+                            TextFont {
+                                font_size: 24.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                            RuleLabel::QuestionMarks,
+                        )]
+                    ),
+                    (
+                        Button,
+                        ButtonType::StrictFlags,
+                        Node {
+                            width: Val::Px(150.),
+                            height: Val::Px(65.),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                        children![(
+                            Text::new(Self::rule_label(&rules, RuleLabel::StrictFlags)),
+                            TextFont {
+                                font_size: 24.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                            RuleLabel::StrictFlags,
+                        )]
+                    ),
+                    (
+                        Button,
+                        ButtonType::NoGuess,
+                        Node {
+                            width: Val::Px(150.),
+                            height: Val::Px(65.),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                        children![(
+                            Text::new(Self::no_guess_label(&no_guess)),
+                            TextFont {
+                                font_size: 24.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                            NoGuessLabel,
+                        )]
+                    ),
+                    (
+                        Button,
+                        ButtonType::Autoplay,
+                        Node {
+                            width: Val::Px(150.),
+                            height: Val::Px(65.),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                        children![(
+                            Text::new(Self::autoplay_label(&autoplay)),
+                            TextFont {
+                                font_size: 24.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                            AutoplayLabel,
+                        )]
+                    ),
+                ],
+            ))
+            .id();
+
+        if SavePlugin::has_save() {
+            let continue_button = commands
+                .spawn((
                     Button,
-                    ButtonType::Hard,
+                    ButtonType::Continue,
                     Node {
                         width: Val::Px(150.),
                         height: Val::Px(65.),
@@ -265,15 +609,16 @@ impl UiPlugin {
                     },
                     BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
                     children![(
-                        Text::new("Hard"),
+                        Text::new("Continue"),
                         TextFont {
                             font_size: 33.0,
                             ..default()
                         },
                         TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                    )]
-                ),
-            ],
-        ));
+                    )],
+                ))
+                .id();
+            commands.entity(root).insert_children(0, &[continue_button]);
+        }
     }
 }
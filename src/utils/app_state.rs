@@ -7,4 +7,6 @@ pub enum AppState {
     InGame,
     Victory,
     Defeat,
+    /// Playing back a recorded game
+    Replay,
 }
@@ -14,6 +14,13 @@ impl Bounds2 {
         }
     }
 
+    pub fn from_min_size(min: Vec2, size: Vec2) -> Self {
+        Self {
+            min,
+            max: min + size,
+        }
+    }
+
     pub fn contains(&self, point: Vec2) -> bool {
         point.x >= self.min.x
             && point.x <= self.max.x
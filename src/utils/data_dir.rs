@@ -0,0 +1,18 @@
+use std::path::PathBuf;
+
+/// Directory holding the files written by the game, such as replays. Falls back to the working
+/// directory on platforms without a user data directory.
+pub fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("bevy-minesweeper")
+}
+
+pub fn replay_dir() -> PathBuf {
+    data_dir().join("replays")
+}
+
+/// File holding the game in progress
+pub fn save_path() -> PathBuf {
+    data_dir().join("save.json")
+}
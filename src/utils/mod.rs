@@ -1,2 +1,3 @@
 pub mod app_state;
 pub mod bounds2;
+pub mod data_dir;